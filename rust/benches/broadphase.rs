@@ -0,0 +1,168 @@
+//! Times `ObstacleWorld` queries with and without the broadphase, checking that both
+//! give the same results.
+//!
+//! Run with `cargo bench --bench broadphase`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use glam::{Quat, Vec3};
+use ik_webgpu::{MeshObstacle, ObstacleWorld, Ray, RayHit, SdfNode, Transform};
+
+const WORLD_SIZES: [usize; 3] = [100, 500, 1000];
+const QUERIES: usize = 2000;
+const WORLD_HALF_SIZE: f32 = 20.0;
+
+/// Small xorshift generator so runs are repeatable without extra dependencies.
+struct Rng(u64);
+
+impl Rng {
+    fn next_f32(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.next_f32()
+    }
+
+    fn point(&mut self) -> Vec3 {
+        Vec3::new(
+            self.range(-WORLD_HALF_SIZE, WORLD_HALF_SIZE),
+            self.range(-WORLD_HALF_SIZE, WORLD_HALF_SIZE),
+            self.range(-WORLD_HALF_SIZE, WORLD_HALF_SIZE),
+        )
+    }
+
+    fn direction(&mut self) -> Vec3 {
+        loop {
+            let v = Vec3::new(
+                self.range(-1.0, 1.0),
+                self.range(-1.0, 1.0),
+                self.range(-1.0, 1.0),
+            );
+            if let Some(v) = v.try_normalize() {
+                return v;
+            }
+        }
+    }
+}
+
+fn build_world(count: usize, rng: &mut Rng) -> ObstacleWorld {
+    let mut world = ObstacleWorld::new();
+    world.add_plane(Vec3::Y, -WORLD_HALF_SIZE);
+    for i in 1..count {
+        let center = rng.point();
+        let size = rng.range(0.2, 1.5);
+        match i % 7 {
+            0 => world.add_sphere(center, size),
+            1 => world.add_box(center, Vec3::new(size, size * 0.5, size * 0.8)),
+            2 => world.add_capsule(center, center + rng.direction() * 2.0, size * 0.5),
+            3 => world.add_oriented_box(
+                Transform::from_position_rotation(center, Quat::from_rotation_y(size)),
+                Vec3::splat(size),
+            ),
+            4 => world.add_cylinder(center, rng.direction(), size * 0.6, size),
+            // An open mesh, whose distances are unsigned.
+            5 => {
+                let (u, v) = (rng.direction() * size, rng.direction() * size);
+                let patch = MeshObstacle::new(
+                    vec![center, center + u, center + u + v, center + v],
+                    vec![[0, 1, 2], [0, 2, 3]],
+                )
+                .expect("patch has area");
+                world.add(patch);
+            }
+            _ => world.add(
+                SdfNode::cuboid(center, Vec3::splat(size))
+                    .subtract(SdfNode::sphere(center + Vec3::splat(size), size))
+                    .rounded(0.05),
+            ),
+        }
+    }
+    world
+}
+
+fn same_hit(a: &Option<(usize, RayHit)>, b: &Option<(usize, RayHit)>) -> bool {
+    match (a, b) {
+        (None, None) => true,
+        (Some((i, x)), Some((j, y))) => {
+            i == j && x.t == y.t && x.point == y.point && x.normal == y.normal
+        }
+        _ => false,
+    }
+}
+
+/// Runs `query` once per input with the broadphase on and off, and returns both times.
+fn compare<I, T>(
+    world: &mut ObstacleWorld,
+    inputs: &[I],
+    query: impl Fn(&ObstacleWorld, &I) -> T,
+    same: impl Fn(&T, &T) -> bool,
+) -> (Duration, Duration) {
+    world.set_broadphase_enabled(false);
+    let start = Instant::now();
+    let linear: Vec<T> = inputs.iter().map(|i| black_box(query(world, i))).collect();
+    let linear_time = start.elapsed();
+
+    world.set_broadphase_enabled(true);
+    // Builds the tree outside the timed loop.
+    black_box(world.closest_obstacle(Vec3::ZERO));
+    let start = Instant::now();
+    let broadphase: Vec<T> = inputs.iter().map(|i| black_box(query(world, i))).collect();
+    let broadphase_time = start.elapsed();
+
+    let mismatches = linear
+        .iter()
+        .zip(&broadphase)
+        .filter(|(a, b)| !same(a, b))
+        .count();
+    assert_eq!(
+        mismatches, 0,
+        "broadphase results differ from the linear path"
+    );
+    (linear_time, broadphase_time)
+}
+
+fn report(name: &str, count: usize, (linear, broadphase): (Duration, Duration)) {
+    let per_query = |d: Duration| d.as_secs_f64() * 1e6 / QUERIES as f64;
+    println!(
+        "{name:<16} {count:>5} obstacles  linear {:>9.2} us  bvh {:>9.2} us  x{:.1}",
+        per_query(linear),
+        per_query(broadphase),
+        linear.as_secs_f64() / broadphase.as_secs_f64(),
+    );
+}
+
+fn main() {
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+
+    for count in WORLD_SIZES {
+        let mut world = build_world(count, &mut rng);
+        let points: Vec<Vec3> = (0..QUERIES).map(|_| rng.point()).collect();
+        let rays: Vec<Ray> = (0..QUERIES)
+            .map(|_| Ray::new(rng.point(), rng.direction()))
+            .collect();
+
+        let times = compare(
+            &mut world,
+            &points,
+            |w, &p| w.push_out_point(p, 0.1),
+            |a, b| a == b,
+        );
+        report("push_out_point", count, times);
+
+        let times = compare(&mut world, &rays, |w, r| w.raycast(r), same_hit);
+        report("raycast", count, times);
+
+        let times = compare(
+            &mut world,
+            &points,
+            |w, &p| w.closest_obstacle(p),
+            |a, b| a == b,
+        );
+        report("closest_obstacle", count, times);
+    }
+}
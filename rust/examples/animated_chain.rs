@@ -1,7 +1,11 @@
-use glam::Vec3;
+use glam::{Quat, Vec3};
 use ik_webgpu::collision::{CollisionConfig, CollisionHit, CollisionResponse, ObstacleWorld};
 use ik_webgpu::dynamics::{SecondOrderDynamics, SpringPreset};
-use ik_webgpu::ik::{Chain, FabrikSolver};
+use ik_webgpu::ik::{
+    BallSocketConstraint, Chain, Constraint, FabrikSolver, HingeConstraint, IkFkBlend, Joint,
+    SwingTwistConstraint,
+};
+use ik_webgpu::math::Transform;
 use ik_webgpu::render::{Camera, CameraController, DebugRenderer, GpuContext, Key, MouseAction};
 use std::sync::Arc;
 use std::time::Instant;
@@ -40,9 +44,22 @@ struct App<'a> {
     response: f32,
     current_preset: usize,
     gui_hovered: bool,
+    selected_joint: usize,
+    ik_blend: IkFkBlend,
+    fk_pose: Vec<Transform>,
 }
 
 const PRESET_NAMES: [&str; 5] = ["Smooth", "Snappy", "Bouncy", "Sluggish", "Anticipate"];
+const CONSTRAINT_NAMES: [&str; 4] = ["None", "Ball", "Hinge", "Swing-Twist"];
+
+enum ChainEdit {
+    InsertAfter(usize),
+    Remove(usize),
+    BoneLength(usize, f32),
+    Rescale(f32),
+    Constraint(usize, usize),
+    ResetToRest,
+}
 
 impl<'a> App<'a> {
     fn new() -> Self {
@@ -72,8 +89,14 @@ impl<'a> App<'a> {
         obstacle_world.add_sphere(Vec3::new(1.5, 2.0, 0.0), 0.5);
         obstacle_world.add_sphere(Vec3::new(-1.0, 1.5, 0.0), 0.4);
         obstacle_world.add_box(Vec3::new(0.0, 3.0, 0.0), Vec3::new(0.8, 0.3, 0.3));
+        obstacle_world.add_oriented_box(
+            Transform::from_position_rotation(Vec3::new(-1.8, 3.0, 0.0), Quat::from_rotation_z(0.6)),
+            Vec3::new(0.4, 0.2, 0.3),
+        );
+        obstacle_world.add_capsule(Vec3::new(1.2, 3.5, -0.3), Vec3::new(2.0, 3.8, 0.3), 0.2);
+        obstacle_world.add_plane(Vec3::Y, -0.5);
 
-        let collision_config = CollisionConfig::new(0.1);
+        let collision_config = CollisionConfig::new(0.1).with_bone_radius(0.05);
 
         let joint_positions: Vec<Vec3> = chain.joints().iter().map(|j| j.position).collect();
         let joint_dynamics: Vec<SecondOrderDynamics<Vec3>> = joint_positions
@@ -82,6 +105,7 @@ impl<'a> App<'a> {
             .collect();
 
         let smoothed_chain = chain.clone();
+        let fk_pose = rest_fk_pose(&chain);
 
         Self {
             window: None,
@@ -112,9 +136,55 @@ impl<'a> App<'a> {
             response: r,
             current_preset: 0,
             gui_hovered: false,
+            selected_joint: 1,
+            ik_blend: IkFkBlend::new(1.0).with_preset(SpringPreset::Smooth),
+            fk_pose,
         }
     }
 
+    fn apply_chain_edit(&mut self, edit: ChainEdit) {
+        match edit {
+            ChainEdit::InsertAfter(index) => {
+                let joints = self.chain.joints();
+                let position = match joints.get(index + 1) {
+                    Some(next) => (joints[index].position + next.position) * 0.5,
+                    None => {
+                        let last = joints[index].position;
+                        let direction = last - joints[index.saturating_sub(1)].position;
+                        last + direction.normalize_or_zero() * 0.8
+                    }
+                };
+                self.chain.insert_joint(index + 1, Joint::new(position));
+                self.selected_joint = index + 1;
+            }
+            ChainEdit::Remove(index) => {
+                self.chain.remove_joint(index);
+                self.selected_joint = index.min(self.chain.joint_count() - 1);
+            }
+            ChainEdit::BoneLength(bone, length) => self.chain.set_bone_length(bone, length),
+            ChainEdit::Rescale(factor) => self.chain.rescale(factor),
+            ChainEdit::Constraint(index, kind) => {
+                let constraint: Option<Box<dyn Constraint>> = match kind {
+                    1 => Some(Box::new(BallSocketConstraint::new(45.0))),
+                    2 => Some(Box::new(HingeConstraint::new(Vec3::Z, -90.0, 90.0))),
+                    3 => Some(Box::new(SwingTwistConstraint::new(45.0, -30.0, 30.0))),
+                    _ => None,
+                };
+                self.chain.set_joint_constraint(index, constraint);
+            }
+            ChainEdit::ResetToRest => self.chain.reset_to_rest(),
+        }
+
+        self.fk_pose = rest_fk_pose(&self.chain);
+        self.smoothed_chain = self.chain.clone();
+        self.joint_dynamics = self
+            .chain
+            .joints()
+            .iter()
+            .map(|j| SecondOrderDynamics::new(8.0, 1.0, 1.0, j.position))
+            .collect();
+    }
+
     fn screen_to_ndc(&self, x: f32, y: f32) -> (f32, f32) {
         let (w, h) = self.window_size;
         let ndc_x = (2.0 * x / w as f32) - 1.0;
@@ -144,21 +214,21 @@ impl<'a> App<'a> {
             self.raw_target
         };
 
+        self.ik_blend.update(dt);
+
         if self.collision_enabled {
-            FabrikSolver::solve_with_collision(
-                &mut self.chain,
-                target,
-                &self.obstacle_world,
-                &self.collision_config,
-            );
+            let (world, config) = (&self.obstacle_world, &self.collision_config);
+            self.ik_blend.apply(&mut self.chain, &self.fk_pose, |chain| {
+                FabrikSolver::solve_with_collision(chain, target, world, config)
+            });
 
-            self.collision_hits = CollisionResponse::get_collision_hits(
+            self.collision_hits = CollisionResponse::get_collision_hits_with(
                 &self.chain,
                 &self.obstacle_world,
-                self.collision_config.margin,
+                &self.collision_config,
             );
         } else {
-            FabrikSolver::solve(&mut self.chain, target);
+            self.ik_blend.solve(&mut self.chain, &self.fk_pose, target);
             self.collision_hits.clear();
         }
 
@@ -202,6 +272,13 @@ impl<'a> App<'a> {
         let mut damping = self.damping;
         let mut response = self.response;
         let mut preset_changed: Option<usize> = None;
+        let mut selected_joint = self.selected_joint;
+        let mut chain_edit: Option<ChainEdit> = None;
+        let joint_count = self.chain.joint_count();
+        let bone_lengths = self.chain.bone_lengths().to_vec();
+        let mut rest_bias = self.chain.rest_bias();
+        let mut ik_enabled = self.ik_blend.target_weight() > 0.5;
+        let ik_weight = self.ik_blend.weight();
 
         let full_output = egui_ctx.run(raw_input, |ctx| {
             egui::Window::new("IK Controls")
@@ -211,6 +288,8 @@ impl<'a> App<'a> {
                     ui.checkbox(&mut dynamics_enabled, "Enable Dynamics");
                     ui.checkbox(&mut collision_enabled, "Enable Collision");
                     ui.checkbox(&mut show_collision_hits, "Show Collision Hits");
+                    ui.checkbox(&mut ik_enabled, "Enable IK");
+                    ui.small(format!("IK weight: {ik_weight:.2}"));
                     ui.separator();
 
                     ui.label("Presets:");
@@ -254,6 +333,59 @@ impl<'a> App<'a> {
                     ui.small("= 1: Immediate");
                     ui.small("> 1: Overshoot");
 
+                    ui.separator();
+                    ui.label("Chain Editing:");
+
+                    ui.horizontal(|ui| {
+                        ui.label("Joint:");
+                        ui.add(egui::Slider::new(&mut selected_joint, 0..=joint_count - 1));
+                    });
+
+                    if let Some(&length) = bone_lengths.get(selected_joint) {
+                        let mut length = length;
+                        ui.horizontal(|ui| {
+                            ui.label("Bone Length:");
+                            if ui.add(egui::Slider::new(&mut length, 0.1..=2.0)).changed() {
+                                chain_edit = Some(ChainEdit::BoneLength(selected_joint, length));
+                            }
+                        });
+                    }
+
+                    ui.horizontal(|ui| {
+                        if ui.button("Insert After").clicked() {
+                            chain_edit = Some(ChainEdit::InsertAfter(selected_joint));
+                        }
+                        if ui
+                            .add_enabled(joint_count > 2, egui::Button::new("Remove"))
+                            .clicked()
+                        {
+                            chain_edit = Some(ChainEdit::Remove(selected_joint));
+                        }
+                        if ui.button("Shrink").clicked() {
+                            chain_edit = Some(ChainEdit::Rescale(0.9));
+                        }
+                        if ui.button("Grow").clicked() {
+                            chain_edit = Some(ChainEdit::Rescale(1.1));
+                        }
+                    });
+
+                    ui.horizontal(|ui| {
+                        ui.label("Constraint:");
+                        for (i, name) in CONSTRAINT_NAMES.iter().enumerate() {
+                            if ui.button(*name).clicked() {
+                                chain_edit = Some(ChainEdit::Constraint(selected_joint, i));
+                            }
+                        }
+                    });
+
+                    ui.horizontal(|ui| {
+                        ui.label("Rest Bias:");
+                        ui.add(egui::Slider::new(&mut rest_bias, 0.0..=1.0));
+                    });
+                    if ui.button("Reset To Rest").clicked() {
+                        chain_edit = Some(ChainEdit::ResetToRest);
+                    }
+
                     ui.separator();
                     ui.label("Controls:");
                     ui.small("Left drag: Move target");
@@ -273,6 +405,13 @@ impl<'a> App<'a> {
         self.collision_enabled = collision_enabled;
         self.show_collision_hits = show_collision_hits;
         self.current_preset = current_preset;
+        self.selected_joint = selected_joint;
+        self.chain.set_rest_bias(rest_bias);
+        self.ik_blend.set_ik_enabled(ik_enabled);
+
+        if let Some(edit) = chain_edit {
+            self.apply_chain_edit(edit);
+        }
 
         if let Some(i) = preset_changed {
             let preset = match i {
@@ -466,15 +605,13 @@ impl ApplicationHandler for App<'_> {
                 }
             }
 
-            WindowEvent::MouseInput { state, button, .. } => {
-                if !self.gui_hovered {
-                    let pressed = state == ElementState::Pressed;
-                    match button {
-                        MouseButton::Left => self.dragging_target = pressed,
-                        MouseButton::Right => self.controller.on_mouse_button(1, pressed),
-                        MouseButton::Middle => self.controller.on_mouse_button(2, pressed),
-                        _ => {}
-                    }
+            WindowEvent::MouseInput { state, button, .. } if !self.gui_hovered => {
+                let pressed = state == ElementState::Pressed;
+                match button {
+                    MouseButton::Left => self.dragging_target = pressed,
+                    MouseButton::Right => self.controller.on_mouse_button(1, pressed),
+                    MouseButton::Middle => self.controller.on_mouse_button(2, pressed),
+                    _ => {}
                 }
             }
 
@@ -492,14 +629,12 @@ impl ApplicationHandler for App<'_> {
                 self.mouse_pos = (x, y);
             }
 
-            WindowEvent::MouseWheel { delta, .. } => {
-                if !self.gui_hovered {
-                    let scroll = match delta {
-                        MouseScrollDelta::LineDelta(_, y) => y,
-                        MouseScrollDelta::PixelDelta(pos) => pos.y as f32 / 50.0,
-                    };
-                    self.controller.on_scroll(scroll);
-                }
+            WindowEvent::MouseWheel { delta, .. } if !self.gui_hovered => {
+                let scroll = match delta {
+                    MouseScrollDelta::LineDelta(_, y) => y,
+                    MouseScrollDelta::PixelDelta(pos) => pos.y as f32 / 50.0,
+                };
+                self.controller.on_scroll(scroll);
             }
 
             WindowEvent::RedrawRequested => {
@@ -515,6 +650,13 @@ impl ApplicationHandler for App<'_> {
     }
 }
 
+/// Local frames of `chain`'s rest pose, used as the FK pose IK blends against.
+fn rest_fk_pose(chain: &Chain) -> Vec<Transform> {
+    let mut rest = chain.clone();
+    rest.reset_to_rest();
+    rest.local_frames().collect()
+}
+
 fn main() {
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
 
@@ -0,0 +1,262 @@
+use glam::Vec3;
+
+use super::raycast::Ray;
+
+/// Items per leaf before a node is split.
+const LEAF_SIZE: usize = 4;
+
+/// An axis-aligned bounding box.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Bounds {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Bounds {
+    pub const EMPTY: Self = Self {
+        min: Vec3::splat(f32::INFINITY),
+        max: Vec3::splat(f32::NEG_INFINITY),
+    };
+
+    pub fn from_points(points: impl IntoIterator<Item = Vec3>) -> Self {
+        points.into_iter().fold(Self::EMPTY, |bounds, point| Self {
+            min: bounds.min.min(point),
+            max: bounds.max.max(point),
+        })
+    }
+
+    pub fn union(self, other: Self) -> Self {
+        Self {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+
+    pub fn padded(self, margin: f32) -> Self {
+        Self {
+            min: self.min - Vec3::splat(margin),
+            max: self.max + Vec3::splat(margin),
+        }
+    }
+
+    pub fn center(self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    /// Distance from `point` to the box, 0 inside it.
+    pub fn distance(self, point: Vec3) -> f32 {
+        (self.min - point)
+            .max(point - self.max)
+            .max(Vec3::ZERO)
+            .length()
+    }
+
+    /// Ray parameter where the ray enters the box within its range, or `None` if it
+    /// misses. Negative when the ray starts inside.
+    pub fn ray_entry(self, ray: &Ray) -> Option<f32> {
+        let inv_dir = ray.direction.recip();
+        let t1 = (self.min - ray.origin) * inv_dir;
+        let t2 = (self.max - ray.origin) * inv_dir;
+        let enter = t1.min(t2).max_element();
+        let exit = t1.max(t2).min_element();
+        (enter <= exit && exit >= ray.t_min && enter <= ray.t_max).then_some(enter)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct BvhNode {
+    bounds: Bounds,
+    /// First entry in `items` for a leaf; index of the right child for an inner node,
+    /// whose left child directly follows it.
+    start: usize,
+    /// Items in a leaf, 0 for an inner node.
+    count: usize,
+}
+
+/// Bounding volume hierarchy over items given by their bounds, split at the median
+/// of the longest axis.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Bvh {
+    nodes: Vec<BvhNode>,
+    items: Vec<usize>,
+}
+
+impl Bvh {
+    pub fn build(bounds: &[Bounds]) -> Self {
+        let mut bvh = Self {
+            nodes: Vec::new(),
+            items: (0..bounds.len()).collect(),
+        };
+        if !bounds.is_empty() {
+            let centers: Vec<Vec3> = bounds.iter().map(|b| b.center()).collect();
+            bvh.split(bounds, &centers, 0, bounds.len());
+        }
+        bvh
+    }
+
+    fn split(&mut self, bounds: &[Bounds], centers: &[Vec3], start: usize, end: usize) -> usize {
+        let index = self.nodes.len();
+        let node_bounds = self.items[start..end]
+            .iter()
+            .fold(Bounds::EMPTY, |acc, &item| acc.union(bounds[item]));
+        self.nodes.push(BvhNode {
+            bounds: node_bounds,
+            start,
+            count: end - start,
+        });
+        if end - start <= LEAF_SIZE {
+            return index;
+        }
+
+        let spread = Bounds::from_points(self.items[start..end].iter().map(|&i| centers[i]));
+        let extent = spread.max - spread.min;
+        let axis = if extent.x >= extent.y && extent.x >= extent.z {
+            0
+        } else if extent.y >= extent.z {
+            1
+        } else {
+            2
+        };
+        let mid = (start + end) / 2;
+        self.items[start..end].select_nth_unstable_by(mid - start, |&a, &b| {
+            centers[a][axis].total_cmp(&centers[b][axis])
+        });
+
+        self.split(bounds, centers, start, mid);
+        let right = self.split(bounds, centers, mid, end);
+        self.nodes[index].start = right;
+        self.nodes[index].count = 0;
+        index
+    }
+
+    /// Recomputes node bounds bottom-up for items that moved, keeping the tree's shape.
+    pub fn refit(&mut self, bounds: &[Bounds]) {
+        // Children always come after their parent, so a reverse sweep sees them first.
+        for index in (0..self.nodes.len()).rev() {
+            let node = &self.nodes[index];
+            let node_bounds = if node.count > 0 {
+                self.items[node.start..node.start + node.count]
+                    .iter()
+                    .fold(Bounds::EMPTY, |acc, &item| acc.union(bounds[item]))
+            } else {
+                self.nodes[index + 1]
+                    .bounds
+                    .union(self.nodes[node.start].bounds)
+            };
+            self.nodes[index].bounds = node_bounds;
+        }
+    }
+
+    /// Item with the smallest `distance` to `point`, lowest index first on ties.
+    /// `distance(item)` must be at least the distance from `point` to the item's bounds
+    /// when `point` is outside them; inside them it may be anything, including negative.
+    pub fn closest(
+        &self,
+        point: Vec3,
+        mut distance: impl FnMut(usize) -> f32,
+    ) -> Option<(usize, f32)> {
+        let mut best: Option<(usize, f32)> = None;
+        let mut stack = Vec::new();
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            // Distance 0 means `point` is inside, where items can be closer than that.
+            if best.is_some_and(|(_, d)| node.bounds.distance(point) > d.max(0.0)) {
+                continue;
+            }
+            if node.count > 0 {
+                for &item in &self.items[node.start..node.start + node.count] {
+                    let d = distance(item);
+                    if best.is_none_or(|(i, best_d)| d < best_d || (d == best_d && item < i)) {
+                        best = Some((item, d));
+                    }
+                }
+                continue;
+            }
+
+            let (left, right) = (index + 1, node.start);
+            let left_d = self.nodes[left].bounds.distance(point);
+            let right_d = self.nodes[right].bounds.distance(point);
+            if left_d <= right_d {
+                stack.extend([right, left]);
+            } else {
+                stack.extend([left, right]);
+            }
+        }
+        best
+    }
+
+    /// Item with the nearest `hit` along `ray`, lowest index first on ties. `hit(item)`
+    /// returns the ray parameter of the item's hit, if any.
+    pub fn raycast(
+        &self,
+        ray: &Ray,
+        mut hit: impl FnMut(usize) -> Option<f32>,
+    ) -> Option<(usize, f32)> {
+        let mut best: Option<(usize, f32)> = None;
+        let mut stack = Vec::new();
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            match node.bounds.ray_entry(ray) {
+                None => continue,
+                Some(enter) if best.is_some_and(|(_, t)| enter > t) => continue,
+                Some(_) => {}
+            }
+            if node.count > 0 {
+                for &item in &self.items[node.start..node.start + node.count] {
+                    if let Some(t) = hit(item) {
+                        if best.is_none_or(|(i, best_t)| t < best_t || (t == best_t && item < i)) {
+                            best = Some((item, t));
+                        }
+                    }
+                }
+                continue;
+            }
+
+            let (left, right) = (index + 1, node.start);
+            let left_t = self.nodes[left]
+                .bounds
+                .ray_entry(ray)
+                .unwrap_or(f32::INFINITY);
+            let right_t = self.nodes[right]
+                .bounds
+                .ray_entry(ray)
+                .unwrap_or(f32::INFINITY);
+            if left_t <= right_t {
+                stack.extend([right, left]);
+            } else {
+                stack.extend([left, right]);
+            }
+        }
+        best
+    }
+
+    /// Calls `visit` for every item whose bounds come within `radius` of `point`.
+    pub fn visit_near(&self, point: Vec3, radius: f32, mut visit: impl FnMut(usize)) {
+        let mut stack = Vec::new();
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if node.bounds.distance(point) > radius {
+                continue;
+            }
+            if node.count > 0 {
+                self.items[node.start..node.start + node.count]
+                    .iter()
+                    .for_each(|&item| visit(item));
+            } else {
+                stack.extend([node.start, index + 1]);
+            }
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use std::fmt;
+use std::io;
+
+/// Why a triangle mesh couldn't be loaded.
+#[derive(Debug)]
+pub enum MeshError {
+    /// The file couldn't be read.
+    Io(io::Error),
+    /// The file extension is neither `.obj` nor `.stl`.
+    UnsupportedFormat,
+    /// Line `line` of a text file couldn't be parsed.
+    Parse { line: usize, reason: &'static str },
+    /// A binary STL file ends before all its triangles.
+    TruncatedStl,
+    /// A face refers to vertex `index`, as written in the source, that doesn't exist.
+    IndexOutOfRange { index: i64, vertex_count: usize },
+    /// The mesh has no triangles with any area.
+    NoTriangles,
+}
+
+impl fmt::Display for MeshError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "couldn't read mesh: {err}"),
+            Self::UnsupportedFormat => write!(f, "mesh format must be OBJ or STL"),
+            Self::Parse { line, reason } => write!(f, "line {line}: {reason}"),
+            Self::TruncatedStl => write!(f, "binary STL is shorter than its triangle count"),
+            Self::IndexOutOfRange {
+                index,
+                vertex_count,
+            } => write!(
+                f,
+                "vertex index {index} is out of range for {vertex_count} vertices"
+            ),
+            Self::NoTriangles => write!(f, "mesh has no non-degenerate triangles"),
+        }
+    }
+}
+
+impl std::error::Error for MeshError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for MeshError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
@@ -0,0 +1,726 @@
+use glam::{IVec3, Vec3};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use super::bvh::{Bounds, Bvh};
+use super::error::MeshError;
+use super::obstacle::{Obstacle, ObstacleShape};
+use super::raycast::{Ray, RayHit};
+
+/// Vertices closer than this fraction of the mesh's size are merged.
+const WELD_TOLERANCE: f32 = 1e-6;
+
+/// Triangle bounds are grown by this much so flat triangles still have a volume.
+const BOUNDS_PADDING: f32 = 1e-5;
+
+/// Which part of a triangle a closest point lies on. Edge `k` runs from corner `k`
+/// to corner `k + 1`.
+#[derive(Debug, Clone, Copy)]
+enum Feature {
+    Vertex(usize),
+    Edge(usize),
+    Face,
+}
+
+/// Closest point to `p` on the triangle `a`, `b`, `c`, and the feature it lies on.
+fn closest_on_triangle(p: Vec3, [a, b, c]: [Vec3; 3]) -> (Vec3, Feature) {
+    let ab = b - a;
+    let ac = c - a;
+    let ap = p - a;
+    let d1 = ab.dot(ap);
+    let d2 = ac.dot(ap);
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return (a, Feature::Vertex(0));
+    }
+
+    let bp = p - b;
+    let d3 = ab.dot(bp);
+    let d4 = ac.dot(bp);
+    if d3 >= 0.0 && d4 <= d3 {
+        return (b, Feature::Vertex(1));
+    }
+
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        let v = d1 / (d1 - d3);
+        return (a + ab * v, Feature::Edge(0));
+    }
+
+    let cp = p - c;
+    let d5 = ab.dot(cp);
+    let d6 = ac.dot(cp);
+    if d6 >= 0.0 && d5 <= d6 {
+        return (c, Feature::Vertex(2));
+    }
+
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        let w = d2 / (d2 - d6);
+        return (a + ac * w, Feature::Edge(2));
+    }
+
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && d4 - d3 >= 0.0 && d5 - d6 >= 0.0 {
+        let w = (d4 - d3) / ((d4 - d3) + (d5 - d6));
+        return (b + (c - b) * w, Feature::Edge(1));
+    }
+
+    let denom = 1.0 / (va + vb + vc);
+    (a + ab * (vb * denom) + ac * (vc * denom), Feature::Face)
+}
+
+/// Ray parameter where `ray` crosses the triangle from either side, within the
+/// ray's range.
+fn ray_triangle(ray: &Ray, [a, b, c]: [Vec3; 3]) -> Option<f32> {
+    let ab = b - a;
+    let ac = c - a;
+    let p = ray.direction.cross(ac);
+    let det = ab.dot(p);
+    if det.abs() < 1e-12 {
+        return None;
+    }
+    let inv_det = 1.0 / det;
+    let ao = ray.origin - a;
+    let u = ao.dot(p) * inv_det;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = ao.cross(ab);
+    let v = ray.direction.dot(q) * inv_det;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    let t = ac.dot(q) * inv_det;
+    (t > ray.t_min && t < ray.t_max).then_some(t)
+}
+
+#[derive(Debug)]
+struct MeshData {
+    vertices: Arc<[Vec3]>,
+    triangles: Arc<[[u32; 3]]>,
+    face_normals: Vec<Vec3>,
+    /// Angle-weighted pseudo-normals, which tell inside from outside when the closest
+    /// point is on a vertex or an edge rather than inside a face.
+    vertex_normals: Vec<Vec3>,
+    edge_normals: Vec<[Vec3; 3]>,
+    /// Whether every edge is shared by at least two triangles.
+    closed: bool,
+    bvh: Bvh,
+    bounds: Bounds,
+}
+
+/// A triangle mesh obstacle, such as level geometry loaded from an OBJ or STL file.
+///
+/// Inside and outside of a closed mesh are told apart with pseudo-normals, so it
+/// should be consistently wound counter-clockwise seen from outside. An open mesh,
+/// such as a terrain patch, has no inside: its distances are unsigned and points are
+/// pushed off whichever side they are on. Queries go through a BVH over the
+/// triangles. Clones share the mesh data.
+#[derive(Debug, Clone)]
+pub struct MeshObstacle {
+    data: Arc<MeshData>,
+}
+
+impl MeshObstacle {
+    /// Builds a mesh from vertices and triangles indexing into them. Vertices at
+    /// (almost) the same position are merged, and triangles without area are dropped.
+    pub fn new(vertices: Vec<Vec3>, triangles: Vec<[u32; 3]>) -> Result<Self, MeshError> {
+        // Merge vertices within a tolerance rather than exactly, so that seams exported
+        // with rounding noise don't leave sliver triangles behind.
+        let extent = Bounds::from_points(vertices.iter().copied());
+        let tolerance =
+            ((extent.max - extent.min).max_element() * WELD_TOLERANCE).max(f32::MIN_POSITIVE);
+        let cell = |v: Vec3| (v / tolerance).floor().as_ivec3();
+
+        let mut welded: Vec<Vec3> = Vec::new();
+        let mut grid: HashMap<IVec3, Vec<u32>> = HashMap::new();
+        let mut remap: Vec<u32> = Vec::with_capacity(vertices.len());
+        for &v in &vertices {
+            let home = cell(v);
+            let neighbours = (-1..=1).flat_map(|x| {
+                (-1..=1).flat_map(move |y| (-1..=1).map(move |z| IVec3::new(x, y, z)))
+            });
+            let existing = neighbours
+                .filter_map(|offset| grid.get(&(home + offset)))
+                .flatten()
+                .find(|&&w| welded[w as usize].distance(v) <= tolerance);
+            let index = match existing {
+                Some(&w) => w,
+                None => {
+                    welded.push(v);
+                    let w = welded.len() as u32 - 1;
+                    grid.entry(home).or_default().push(w);
+                    w
+                }
+            };
+            remap.push(index);
+        }
+
+        let mut kept: Vec<[u32; 3]> = Vec::with_capacity(triangles.len());
+        for triangle in triangles {
+            if let Some(&index) = triangle.iter().find(|&&i| i as usize >= remap.len()) {
+                return Err(MeshError::IndexOutOfRange {
+                    index: index as i64,
+                    vertex_count: remap.len(),
+                });
+            }
+            let triangle = triangle.map(|i| remap[i as usize]);
+            let [a, b, c] = triangle.map(|i| welded[i as usize]);
+            if (b - a).cross(c - a).length_squared() > 0.0 {
+                kept.push(triangle);
+            }
+        }
+        if kept.is_empty() {
+            return Err(MeshError::NoTriangles);
+        }
+
+        let corners = |t: &[u32; 3]| t.map(|i| welded[i as usize]);
+        let face_normals: Vec<Vec3> = kept
+            .iter()
+            .map(|t| {
+                let [a, b, c] = corners(t);
+                (b - a).cross(c - a).normalize()
+            })
+            .collect();
+
+        let mut vertex_normals = vec![Vec3::ZERO; welded.len()];
+        let mut edge_sums: HashMap<(u32, u32), (Vec3, usize)> = HashMap::new();
+        for (triangle, &normal) in kept.iter().zip(&face_normals) {
+            let points = corners(triangle);
+            for k in 0..3 {
+                let (prev, here, next) = (points[(k + 2) % 3], points[k], points[(k + 1) % 3]);
+                let angle = (prev - here).angle_between(next - here);
+                vertex_normals[triangle[k] as usize] += normal * angle;
+
+                let (i, j) = (triangle[k], triangle[(k + 1) % 3]);
+                let (sum, uses) = edge_sums.entry((i.min(j), i.max(j))).or_default();
+                *sum += normal;
+                *uses += 1;
+            }
+        }
+        let edge_normals: Vec<[Vec3; 3]> = kept
+            .iter()
+            .map(|t| {
+                [0, 1, 2].map(|k| {
+                    let (i, j) = (t[k], t[(k + 1) % 3]);
+                    edge_sums[&(i.min(j), i.max(j))].0
+                })
+            })
+            .collect();
+        let closed = edge_sums.values().all(|&(_, uses)| uses >= 2);
+
+        let triangle_bounds: Vec<Bounds> = kept
+            .iter()
+            .map(|t| Bounds::from_points(corners(t)).padded(BOUNDS_PADDING))
+            .collect();
+        let bounds = Bounds::from_points(welded.iter().copied());
+
+        Ok(Self {
+            data: Arc::new(MeshData {
+                vertices: welded.into(),
+                triangles: kept.into(),
+                face_normals,
+                vertex_normals,
+                edge_normals,
+                closed,
+                bvh: Bvh::build(&triangle_bounds),
+                bounds,
+            }),
+        })
+    }
+
+    /// Loads an `.obj` or `.stl` file, picked by extension.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, MeshError> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_ascii_lowercase);
+        match extension.as_deref() {
+            Some("obj") => Self::from_obj(&std::fs::read_to_string(path)?),
+            Some("stl") => Self::from_stl(&std::fs::read(path)?),
+            _ => Err(MeshError::UnsupportedFormat),
+        }
+    }
+
+    /// Parses the vertices and faces of a Wavefront OBJ file. Polygons are split into
+    /// triangle fans; everything else is ignored.
+    pub fn from_obj(source: &str) -> Result<Self, MeshError> {
+        let mut vertices = Vec::new();
+        let mut triangles = Vec::new();
+
+        for (i, text) in source.lines().enumerate() {
+            let line = i + 1;
+            let mut tokens = text.split_whitespace();
+            match tokens.next() {
+                Some("v") => vertices.push(parse_vec3(&mut tokens, line)?),
+                Some("f") => {
+                    let corners = tokens
+                        .map(|token| obj_index(token, vertices.len(), line))
+                        .collect::<Result<Vec<u32>, _>>()?;
+                    if corners.len() < 3 {
+                        return Err(MeshError::Parse {
+                            line,
+                            reason: "face needs at least three vertices",
+                        });
+                    }
+                    for k in 1..corners.len() - 1 {
+                        triangles.push([corners[0], corners[k], corners[k + 1]]);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Self::new(vertices, triangles)
+    }
+
+    /// Parses an ASCII or binary STL file.
+    pub fn from_stl(bytes: &[u8]) -> Result<Self, MeshError> {
+        // Sized in `u64` so a corrupt count can't overflow on 32-bit targets.
+        let binary_len = match bytes.get(80..84) {
+            Some(b) => Some(
+                u64::from(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                    .checked_mul(50)
+                    .and_then(|len| len.checked_add(84))
+                    .ok_or(MeshError::TruncatedStl)?,
+            ),
+            None => None,
+        };
+        let len = bytes.len() as u64;
+
+        if binary_len == Some(len) {
+            return Self::from_binary_stl(bytes);
+        }
+        if bytes.starts_with(b"solid") {
+            if let Ok(text) = std::str::from_utf8(bytes) {
+                return Self::from_ascii_stl(text);
+            }
+        }
+        if binary_len.is_some_and(|binary_len| len > binary_len) {
+            return Self::from_binary_stl(bytes);
+        }
+        Err(MeshError::TruncatedStl)
+    }
+
+    /// Only called once `bytes` is known to hold every record, so offsets fit in `usize`.
+    fn from_binary_stl(bytes: &[u8]) -> Result<Self, MeshError> {
+        let count = u32::from_le_bytes([bytes[80], bytes[81], bytes[82], bytes[83]]) as usize;
+        let read_f32 = |at: usize| {
+            f32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]])
+        };
+
+        let mut vertices = Vec::with_capacity(count * 3);
+        for i in 0..count {
+            // Each record is a normal, three corners and a two byte attribute count.
+            let record = 84 + i * 50 + 12;
+            for corner in 0..3 {
+                let at = record + corner * 12;
+                vertices.push(Vec3::new(read_f32(at), read_f32(at + 4), read_f32(at + 8)));
+            }
+        }
+        let triangles = (0..count as u32)
+            .map(|i| [3 * i, 3 * i + 1, 3 * i + 2])
+            .collect();
+        Self::new(vertices, triangles)
+    }
+
+    fn from_ascii_stl(text: &str) -> Result<Self, MeshError> {
+        let mut vertices = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let mut tokens = line.split_whitespace();
+            if tokens.next() == Some("vertex") {
+                vertices.push(parse_vec3(&mut tokens, i + 1)?);
+            }
+        }
+        let triangles = (0..(vertices.len() / 3) as u32)
+            .map(|i| [3 * i, 3 * i + 1, 3 * i + 2])
+            .collect();
+        Self::new(vertices, triangles)
+    }
+
+    /// Vertices after merging duplicates.
+    pub fn vertices(&self) -> &[Vec3] {
+        &self.data.vertices
+    }
+
+    pub fn triangles(&self) -> &[[u32; 3]] {
+        &self.data.triangles
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.data.triangles.len()
+    }
+
+    /// Whether every edge is shared by two or more triangles, so the mesh has an
+    /// inside. Open meshes report unsigned distances.
+    pub fn is_closed(&self) -> bool {
+        self.data.closed
+    }
+
+    fn corners(&self, triangle: usize) -> [Vec3; 3] {
+        self.data.triangles[triangle].map(|i| self.data.vertices[i as usize])
+    }
+
+    /// Closest point on the mesh to `point`, with the pseudo-normal of the feature it
+    /// lies on.
+    fn closest(&self, point: Vec3) -> (Vec3, Vec3) {
+        let (triangle, _) = self
+            .data
+            .bvh
+            .closest(point, |t| {
+                closest_on_triangle(point, self.corners(t))
+                    .0
+                    .distance(point)
+            })
+            .expect("a mesh has at least one triangle");
+
+        let (closest, feature) = closest_on_triangle(point, self.corners(triangle));
+        let normal = match feature {
+            Feature::Vertex(k) => {
+                self.data.vertex_normals[self.data.triangles[triangle][k] as usize]
+            }
+            Feature::Edge(k) => self.data.edge_normals[triangle][k],
+            Feature::Face => self.data.face_normals[triangle],
+        };
+        (closest, normal)
+    }
+}
+
+fn parse_vec3<'a>(
+    tokens: &mut impl Iterator<Item = &'a str>,
+    line: usize,
+) -> Result<Vec3, MeshError> {
+    let mut coord = || {
+        tokens
+            .next()
+            .and_then(|t| t.parse::<f32>().ok())
+            .ok_or(MeshError::Parse {
+                line,
+                reason: "expected three coordinates",
+            })
+    };
+    Ok(Vec3::new(coord()?, coord()?, coord()?))
+}
+
+/// Resolves an OBJ face corner such as `3`, `3/1/2` or `-1` to a 0-based vertex index.
+fn obj_index(token: &str, vertex_count: usize, line: usize) -> Result<u32, MeshError> {
+    let index: i64 =
+        token
+            .split('/')
+            .next()
+            .and_then(|s| s.parse().ok())
+            .ok_or(MeshError::Parse {
+                line,
+                reason: "invalid face index",
+            })?;
+    let resolved = if index < 0 {
+        vertex_count as i64 + index
+    } else {
+        index - 1
+    };
+    if resolved < 0 || resolved >= vertex_count as i64 {
+        return Err(MeshError::IndexOutOfRange {
+            index,
+            vertex_count,
+        });
+    }
+    Ok(resolved as u32)
+}
+
+impl Obstacle for MeshObstacle {
+    fn contains_point(&self, point: Vec3) -> bool {
+        self.signed_distance(point) <= 0.0
+    }
+
+    fn signed_distance(&self, point: Vec3) -> f32 {
+        let (closest, normal) = self.closest(point);
+        let distance = point.distance(closest);
+        if self.data.closed && (point - closest).dot(normal) < 0.0 {
+            -distance
+        } else {
+            distance
+        }
+    }
+
+    fn closest_surface_point(&self, point: Vec3) -> Vec3 {
+        self.closest(point).0
+    }
+
+    fn surface_normal(&self, point: Vec3) -> Vec3 {
+        self.closest(point).1.normalize_or_zero()
+    }
+
+    fn ray_intersect(&self, ray: &Ray) -> Option<RayHit> {
+        let (triangle, t) = self
+            .data
+            .bvh
+            .raycast(ray, |t| ray_triangle(ray, self.corners(t)))?;
+        Some(RayHit {
+            t,
+            point: ray.at(t),
+            normal: self.data.face_normals[triangle],
+        })
+    }
+
+    fn push_out(&self, point: Vec3, margin: f32) -> Vec3 {
+        let (closest, normal) = self.closest(point);
+        let offset = point - closest;
+        let outward = if offset.length_squared() < 1e-12 {
+            normal.normalize_or_zero()
+        } else if self.data.closed && offset.dot(normal) < 0.0 {
+            -offset.normalize()
+        } else {
+            offset.normalize()
+        };
+        closest + outward * margin
+    }
+
+    fn clone_box(&self) -> Box<dyn Obstacle> {
+        Box::new(self.clone())
+    }
+
+    fn center(&self) -> Vec3 {
+        self.data.bounds.center()
+    }
+
+    fn render_shape(&self) -> ObstacleShape {
+        ObstacleShape::Mesh {
+            vertices: Arc::clone(&self.data.vertices),
+            triangles: Arc::clone(&self.data.triangles),
+        }
+    }
+
+    fn bounds(&self) -> Option<(Vec3, Vec3)> {
+        Some((self.data.bounds.min, self.data.bounds.max))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TETRAHEDRON_OBJ: &str = "\
+v 0 0 0
+v 1 0 0
+v 0 1 0
+v 0 0 1
+f 1 3 2
+f 1 2 4
+f 1 4 3
+f 2 3 4
+";
+
+    fn binary_stl(header: &[u8], triangles: &[[Vec3; 3]]) -> Vec<u8> {
+        let mut bytes = header.to_vec();
+        bytes.resize(80, b' ');
+        bytes.extend_from_slice(&(triangles.len() as u32).to_le_bytes());
+        for triangle in triangles {
+            bytes.extend_from_slice(&[0; 12]);
+            for corner in triangle {
+                for coord in corner.to_array() {
+                    bytes.extend_from_slice(&coord.to_le_bytes());
+                }
+            }
+            bytes.extend_from_slice(&[0; 2]);
+        }
+        bytes
+    }
+
+    #[test]
+    fn obj_closed_mesh() {
+        let mesh = MeshObstacle::from_obj(TETRAHEDRON_OBJ).unwrap();
+        assert_eq!(mesh.vertices().len(), 4);
+        assert_eq!(mesh.triangle_count(), 4);
+        assert!(mesh.is_closed());
+        assert!(mesh.contains_point(Vec3::splat(0.1)));
+        assert!(!mesh.contains_point(Vec3::splat(1.0)));
+    }
+
+    #[test]
+    fn obj_negative_indices_count_back_from_latest_vertex() {
+        let source = "v 0 0 0\nv 1 0 0\nv 0 1 0\nf -3 -2 -1\n";
+        let mesh = MeshObstacle::from_obj(source).unwrap();
+        assert_eq!(mesh.triangles(), &[[0, 1, 2]]);
+    }
+
+    #[test]
+    fn obj_ignores_texture_and_normal_indices() {
+        let source = "v 0 0 0\nv 1 0 0\nv 0 1 0\nvn 0 0 1\nf 1//1 2//1 3//1\n";
+        let mesh = MeshObstacle::from_obj(source).unwrap();
+        assert_eq!(mesh.triangles(), &[[0, 1, 2]]);
+
+        let source = "v 0 0 0\nv 1 0 0\nv 0 1 0\nvt 0 0\nf 1/1/1 2/1/1 3/1/1\n";
+        let mesh = MeshObstacle::from_obj(source).unwrap();
+        assert_eq!(mesh.triangles(), &[[0, 1, 2]]);
+    }
+
+    #[test]
+    fn obj_polygons_become_triangle_fans() {
+        let source = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nf 1 2 3 4\n";
+        let mesh = MeshObstacle::from_obj(source).unwrap();
+        assert_eq!(mesh.triangles(), &[[0, 1, 2], [0, 2, 3]]);
+    }
+
+    #[test]
+    fn obj_out_of_range_indices() {
+        let source = "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 4\n";
+        assert!(matches!(
+            MeshObstacle::from_obj(source),
+            Err(MeshError::IndexOutOfRange {
+                index: 4,
+                vertex_count: 3
+            })
+        ));
+
+        let source = "v 0 0 0\nv 1 0 0\nv 0 1 0\nf -4 1 2\n";
+        assert!(matches!(
+            MeshObstacle::from_obj(source),
+            Err(MeshError::IndexOutOfRange {
+                index: -4,
+                vertex_count: 3
+            })
+        ));
+
+        // Indices only see the vertices defined above the face.
+        let source = "v 0 0 0\nv 1 0 0\nf 1 2 3\nv 0 1 0\n";
+        assert!(matches!(
+            MeshObstacle::from_obj(source),
+            Err(MeshError::IndexOutOfRange { index: 3, .. })
+        ));
+
+        let source = "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 0 1 2\n";
+        assert!(matches!(
+            MeshObstacle::from_obj(source),
+            Err(MeshError::IndexOutOfRange { index: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn obj_parse_errors_report_their_line() {
+        assert!(matches!(
+            MeshObstacle::from_obj("v 0 0 0\nv 1 x 0\n"),
+            Err(MeshError::Parse { line: 2, .. })
+        ));
+        assert!(matches!(
+            MeshObstacle::from_obj("v 0 0 0\nv 1 0 0\nf 1 2\n"),
+            Err(MeshError::Parse { line: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn empty_meshes_are_rejected() {
+        assert!(matches!(
+            MeshObstacle::from_obj(""),
+            Err(MeshError::NoTriangles)
+        ));
+        assert!(matches!(
+            MeshObstacle::from_obj("v 0 0 0\nv 1 0 0\nv 0 1 0\n"),
+            Err(MeshError::NoTriangles)
+        ));
+        // Collinear corners leave no triangle with any area.
+        assert!(matches!(
+            MeshObstacle::from_obj("v 0 0 0\nv 1 0 0\nv 2 0 0\nf 1 2 3\n"),
+            Err(MeshError::NoTriangles)
+        ));
+        assert!(matches!(
+            MeshObstacle::from_stl(&binary_stl(b"empty", &[])),
+            Err(MeshError::NoTriangles)
+        ));
+        assert!(matches!(
+            MeshObstacle::new(Vec::new(), Vec::new()),
+            Err(MeshError::NoTriangles)
+        ));
+    }
+
+    #[test]
+    fn new_rejects_out_of_range_indices() {
+        let vertices = vec![Vec3::ZERO, Vec3::X, Vec3::Y];
+        assert!(matches!(
+            MeshObstacle::new(vertices, vec![[0, 1, 5]]),
+            Err(MeshError::IndexOutOfRange {
+                index: 5,
+                vertex_count: 3
+            })
+        ));
+    }
+
+    #[test]
+    fn welds_duplicate_and_nearby_vertices() {
+        // Two triangles of a quad, each with its own copy of the shared edge; one copy
+        // is off by rounding noise.
+        let vertices = vec![
+            Vec3::ZERO,
+            Vec3::X,
+            Vec3::new(1.0, 1.0, 0.0),
+            Vec3::ZERO,
+            Vec3::new(1.0, 1.0 + 5e-7, 0.0),
+            Vec3::Y,
+        ];
+        let mesh = MeshObstacle::new(vertices, vec![[0, 1, 2], [3, 4, 5]]).unwrap();
+        assert_eq!(mesh.vertices().len(), 4);
+        assert_eq!(mesh.triangles(), &[[0, 1, 2], [0, 2, 3]]);
+
+        // Vertices further apart than the tolerance stay separate.
+        let vertices = vec![Vec3::ZERO, Vec3::X, Vec3::Y, Vec3::new(0.0, 0.0, 0.01)];
+        let mesh = MeshObstacle::new(vertices, vec![[0, 1, 2], [3, 1, 2]]).unwrap();
+        assert_eq!(mesh.vertices().len(), 4);
+    }
+
+    #[test]
+    fn ascii_stl() {
+        let source = "\
+solid tri
+  facet normal 0 0 1
+    outer loop
+      vertex 0 0 0
+      vertex 1 0 0
+      vertex 0 1 0
+    endloop
+  endfacet
+  facet normal 0 0 1
+    outer loop
+      vertex 1 0 0
+      vertex 1 1 0
+      vertex 0 1 0
+    endloop
+  endfacet
+endsolid tri
+";
+        let mesh = MeshObstacle::from_stl(source.as_bytes()).unwrap();
+        assert_eq!(mesh.triangle_count(), 2);
+        // STL repeats shared corners, which welding merges.
+        assert_eq!(mesh.vertices().len(), 4);
+        assert!(!mesh.is_closed());
+    }
+
+    #[test]
+    fn binary_stl_with_solid_header() {
+        // Many exporters start binary headers with "solid", so the size decides.
+        let triangles = [
+            [Vec3::ZERO, Vec3::X, Vec3::Y],
+            [Vec3::X, Vec3::new(1.0, 1.0, 0.0), Vec3::Y],
+        ];
+        for header in [&b"solid exported by a binary writer"[..], b"binary"] {
+            let mesh = MeshObstacle::from_stl(&binary_stl(header, &triangles)).unwrap();
+            assert_eq!(mesh.triangle_count(), 2);
+            assert_eq!(mesh.vertices().len(), 4);
+        }
+    }
+
+    #[test]
+    fn truncated_binary_stl() {
+        let triangles = [[Vec3::ZERO, Vec3::X, Vec3::Y]];
+        let mut bytes = binary_stl(b"binary", &triangles);
+        bytes.truncate(bytes.len() - 10);
+        assert!(matches!(
+            MeshObstacle::from_stl(&bytes),
+            Err(MeshError::TruncatedStl)
+        ));
+        assert!(matches!(
+            MeshObstacle::from_stl(&bytes[..40]),
+            Err(MeshError::TruncatedStl)
+        ));
+    }
+}
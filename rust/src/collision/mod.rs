@@ -1,9 +1,20 @@
+mod bvh;
+mod error;
+mod mesh;
 mod obstacle;
 mod raycast;
 mod response;
+mod sdf;
+mod shapes;
 mod world;
 
-pub use obstacle::{AabbObstacle, Obstacle, ObstacleShape, SphereObstacle};
+pub use error::MeshError;
+pub use mesh::MeshObstacle;
+pub use obstacle::{AabbObstacle, Obstacle, ObstacleShape, SegmentContact, SphereObstacle};
 pub use raycast::{Ray, RayHit};
 pub use response::{CollisionConfig, CollisionHit, CollisionResponse};
+pub use sdf::SdfNode;
+pub use shapes::{
+    CapsuleObstacle, ConvexHullObstacle, CylinderObstacle, OrientedBoxObstacle, PlaneObstacle,
+};
 pub use world::ObstacleWorld;
\ No newline at end of file
@@ -1,12 +1,92 @@
-use glam::Vec3;
+use glam::{Quat, Vec3};
 use std::fmt::Debug;
+use std::sync::Arc;
 
 use super::raycast::{Ray, RayHit};
+use crate::math::Transform;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub enum ObstacleShape {
     Sphere { center: Vec3, radius: f32 },
     Box { center: Vec3, half_extents: Vec3 },
+    Capsule { start: Vec3, end: Vec3, radius: f32 },
+    OrientedBox { center: Vec3, rotation: Quat, half_extents: Vec3 },
+    Plane { normal: Vec3, offset: f32 },
+    Cylinder { center: Vec3, axis: Vec3, radius: f32, half_height: f32 },
+    /// Hull corners, and pairs of indices into them for the hull's edges.
+    ConvexHull { vertices: Vec<Vec3>, edges: Vec<[usize; 2]> },
+    /// Shared with the obstacle, so drawing a large mesh doesn't copy it.
+    Mesh { vertices: Arc<[Vec3]>, triangles: Arc<[[u32; 3]]> },
+    Group(Vec<ObstacleShape>),
+}
+
+impl ObstacleShape {
+    /// The shape moved by `transform`. Non-uniform scale is approximated by the
+    /// largest axis scale for everything but boxes and point lists.
+    pub fn transformed(&self, transform: &Transform) -> Self {
+        let scale = transform.scale.abs().max_element();
+        let point = |p: Vec3| transform.transform_point(p);
+        match self {
+            Self::Sphere { center, radius } => Self::Sphere {
+                center: point(*center),
+                radius: radius * scale,
+            },
+            Self::Box {
+                center,
+                half_extents,
+            } => Self::OrientedBox {
+                center: point(*center),
+                rotation: transform.rotation,
+                half_extents: *half_extents * transform.scale.abs(),
+            },
+            Self::Capsule { start, end, radius } => Self::Capsule {
+                start: point(*start),
+                end: point(*end),
+                radius: radius * scale,
+            },
+            Self::OrientedBox {
+                center,
+                rotation,
+                half_extents,
+            } => Self::OrientedBox {
+                center: point(*center),
+                rotation: transform.rotation * *rotation,
+                half_extents: *half_extents * scale,
+            },
+            Self::Plane { normal, offset } => {
+                let normal_out = transform.rotation * *normal;
+                Self::Plane {
+                    normal: normal_out,
+                    offset: normal_out.dot(point(*normal * *offset)),
+                }
+            }
+            Self::Cylinder {
+                center,
+                axis,
+                radius,
+                half_height,
+            } => Self::Cylinder {
+                center: point(*center),
+                axis: transform.rotation * *axis,
+                radius: radius * scale,
+                half_height: half_height * scale,
+            },
+            Self::ConvexHull { vertices, edges } => Self::ConvexHull {
+                vertices: vertices.iter().map(|&v| point(v)).collect(),
+                edges: edges.clone(),
+            },
+            Self::Mesh {
+                vertices,
+                triangles,
+            } => Self::Mesh {
+                vertices: vertices.iter().map(|&v| point(v)).collect(),
+                triangles: Arc::clone(triangles),
+            },
+            Self::Group(shapes) => {
+                Self::Group(shapes.iter().map(|s| s.transformed(transform)).collect())
+            }
+        }
+    }
 }
 
 pub trait Obstacle: Send + Sync + Debug {
@@ -19,6 +99,68 @@ pub trait Obstacle: Send + Sync + Debug {
     fn clone_box(&self) -> Box<dyn Obstacle>;
     fn center(&self) -> Vec3;
     fn render_shape(&self) -> ObstacleShape;
+
+    /// Parameter in `[0, 1]` of the point on the segment `a`–`b` with the smallest
+    /// signed distance, i.e. where a bone along it comes closest to or cuts deepest
+    /// into the obstacle.
+    fn closest_segment_param(&self, a: Vec3, b: Vec3) -> f32 {
+        deepest_along_segment(|p| self.signed_distance(p), a, b)
+    }
+
+    /// Axis-aligned `(min, max)` corners around the obstacle, or `None` if it is
+    /// unbounded. [`ObstacleWorld`](super::ObstacleWorld) culls queries with these
+    /// boxes, so `signed_distance` must never be smaller than the distance to the box.
+    fn bounds(&self) -> Option<(Vec3, Vec3)> {
+        None
+    }
+}
+
+/// How far a segment, thickened into a capsule, cuts into an obstacle.
+#[derive(Debug, Clone, Copy)]
+pub struct SegmentContact {
+    /// Index of the obstacle in its [`ObstacleWorld`](super::ObstacleWorld).
+    pub obstacle: usize,
+    /// Segment parameter of the deepest point, from 0 at the start to 1 at the end.
+    pub t: f32,
+    /// Deepest point on the segment.
+    pub point: Vec3,
+    /// Direction to move the segment in to clear the obstacle.
+    pub normal: Vec3,
+    /// How far the segment must move along `normal` to clear the obstacle.
+    pub depth: f32,
+}
+
+/// Intervals the segment is sampled in before refining around the best sample.
+const SEGMENT_SAMPLES: usize = 8;
+const SEGMENT_REFINE_STEPS: usize = 16;
+
+/// Minimises `distance` along `a`–`b` by sampling, then golden-section search around
+/// the best sample. Exact for convex obstacles, a good local answer for the rest.
+pub(crate) fn deepest_along_segment(distance: impl Fn(Vec3) -> f32, a: Vec3, b: Vec3) -> f32 {
+    let at = |t: f32| distance(a.lerp(b, t));
+    let step = 1.0 / SEGMENT_SAMPLES as f32;
+    let best = (0..=SEGMENT_SAMPLES)
+        .map(|i| i as f32 * step)
+        .min_by(|x, y| at(*x).total_cmp(&at(*y)))
+        .unwrap_or(0.0);
+
+    let ratio = 0.5 * (5.0f32.sqrt() - 1.0);
+    let (mut lo, mut hi) = ((best - step).max(0.0), (best + step).min(1.0));
+    for _ in 0..SEGMENT_REFINE_STEPS {
+        let x1 = hi - ratio * (hi - lo);
+        let x2 = lo + ratio * (hi - lo);
+        if at(x1) < at(x2) {
+            hi = x2;
+        } else {
+            lo = x1;
+        }
+    }
+    let refined = 0.5 * (lo + hi);
+    if at(refined) < at(best) {
+        refined
+    } else {
+        best
+    }
 }
 
 impl Clone for Box<dyn Obstacle> {
@@ -112,6 +254,20 @@ impl Obstacle for SphereObstacle {
             radius: self.radius,
         }
     }
+
+    fn closest_segment_param(&self, a: Vec3, b: Vec3) -> f32 {
+        let ab = b - a;
+        let length_squared = ab.length_squared();
+        if length_squared < 1e-12 {
+            return 0.0;
+        }
+        ((self.center - a).dot(ab) / length_squared).clamp(0.0, 1.0)
+    }
+
+    fn bounds(&self) -> Option<(Vec3, Vec3)> {
+        let extent = Vec3::splat(self.radius);
+        Some((self.center - extent, self.center + extent))
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -258,4 +414,8 @@ impl Obstacle for AabbObstacle {
             half_extents: self.half_extents(),
         }
     }
+
+    fn bounds(&self) -> Option<(Vec3, Vec3)> {
+        Some((self.min, self.max))
+    }
 }
\ No newline at end of file
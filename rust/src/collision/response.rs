@@ -3,11 +3,23 @@ use glam::Vec3;
 use super::world::ObstacleWorld;
 use crate::ik::Chain;
 
+/// Smallest lever, as a share of the bone, used when turning a bone about its start
+/// joint; contacts closer to the start than this are mostly the start joint's own.
+const MIN_BONE_LEVER: f32 = 0.1;
+
+/// Bone hits this close to either end of the bone are left to the joint hits.
+const BONE_HIT_END_SLACK: f32 = 0.01;
+
+/// Bones cutting into an obstacle by less than this are left alone.
+const MIN_BONE_DEPTH: f32 = 1e-4;
+
 #[derive(Debug, Clone, Copy)]
 pub struct CollisionConfig {
     pub margin: f32,
     pub max_iterations: u32,
     pub preserve_bone_lengths: bool,
+    /// Radius of the capsule around every bone, added to `margin` for joints as well.
+    pub bone_radius: f32,
 }
 
 impl Default for CollisionConfig {
@@ -16,6 +28,7 @@ impl Default for CollisionConfig {
             margin: 0.05,
             max_iterations: 4,
             preserve_bone_lengths: true,
+            bone_radius: 0.0,
         }
     }
 }
@@ -37,34 +50,50 @@ impl CollisionConfig {
         self.preserve_bone_lengths = preserve;
         self
     }
+
+    pub fn with_bone_radius(mut self, radius: f32) -> Self {
+        self.bone_radius = radius.max(0.0);
+        self
+    }
+
+    /// Clearance kept between obstacles and the bone and joint centres.
+    pub fn clearance(&self) -> f32 {
+        self.margin + self.bone_radius
+    }
 }
 
 pub struct CollisionResponse;
 
 impl CollisionResponse {
-    pub fn resolve_chain(chain: &mut Chain, world: &ObstacleWorld, config: &CollisionConfig) {
+    /// Pushes joints out of obstacles, then turns every bone that still cuts into one
+    /// about its start joint until it clears, carrying the rest of the chain along so
+    /// bone lengths are kept. Bones are capsules of [`CollisionConfig::bone_radius`].
+    /// Returns whether anything had to move.
+    pub fn resolve_chain(
+        chain: &mut Chain,
+        world: &ObstacleWorld,
+        config: &CollisionConfig,
+    ) -> bool {
         if world.is_empty() {
-            return;
+            return false;
         }
 
         let n = chain.joint_count();
         if n < 2 {
-            return;
+            return false;
         }
 
+        let mut collided = false;
         for _ in 0..config.max_iterations {
             let mut any_collision = false;
 
-            {
-                let joints = chain.joints_mut();
-                for i in 1..n {
-                    let old_pos = joints[i].position;
-                    let new_pos = world.push_out_point(old_pos, config.margin);
+            for joint in chain.joints_mut().iter_mut().skip(1) {
+                let old_pos = joint.position;
+                let new_pos = world.push_out_point(old_pos, config.clearance());
 
-                    if (new_pos - old_pos).length_squared() > 0.0001 {
-                        joints[i].position = new_pos;
-                        any_collision = true;
-                    }
+                if (new_pos - old_pos).length_squared() > 0.0001 {
+                    joint.position = new_pos;
+                    any_collision = true;
                 }
             }
 
@@ -72,14 +101,51 @@ impl CollisionResponse {
                 Self::fix_bone_lengths(chain);
             }
 
+            for bone in 0..n - 1 {
+                any_collision |= Self::push_out_bone(chain, bone, world, config);
+            }
+
             if !any_collision {
                 break;
             }
+            collided = true;
+        }
+        collided
+    }
+
+    fn push_out_bone(
+        chain: &mut Chain,
+        bone: usize,
+        world: &ObstacleWorld,
+        config: &CollisionConfig,
+    ) -> bool {
+        let joints = chain.joints();
+        let (start, end) = (joints[bone].position, joints[bone + 1].position);
+        let Some(contact) = world.segment_penetration(start, end, config.clearance()) else {
+            return false;
+        };
+        if contact.depth <= MIN_BONE_DEPTH {
+            return false;
         }
+
+        // Moving the end by `push / t` moves the contact point by about `push`.
+        let push = contact.normal * contact.depth / contact.t.max(MIN_BONE_LEVER);
+        let length = (end - start).length();
+        let Some(direction) = (end + push - start).try_normalize() else {
+            return false;
+        };
+        let offset = start + direction * length - end;
+
+        for joint in &mut chain.joints_mut()[bone + 1..] {
+            joint.position += offset;
+        }
+        true
     }
 
     fn fix_bone_lengths(chain: &mut Chain) {
-        let bone_lengths: Vec<f32> = chain.bone_lengths().to_vec();
+        let bone_lengths: Vec<f32> = (0..chain.bone_lengths().len())
+            .map(|bone| chain.bone_length(bone))
+            .collect();
         let joints = chain.joints_mut();
         let n = joints.len();
 
@@ -114,16 +180,29 @@ impl CollisionResponse {
             .collect()
     }
 
+    /// Joints that sit within `margin` of an obstacle, and bones that cut into one
+    /// between their joints. Bones have the default radius of [`CollisionConfig`].
     pub fn get_collision_hits(
         chain: &Chain,
         world: &ObstacleWorld,
         margin: f32,
+    ) -> Vec<CollisionHit> {
+        Self::get_collision_hits_with(chain, world, &CollisionConfig::new(margin))
+    }
+
+    /// Joints that sit inside the clearance of an obstacle, and bones whose capsule cuts
+    /// into one between their joints.
+    pub fn get_collision_hits_with(
+        chain: &Chain,
+        world: &ObstacleWorld,
+        config: &CollisionConfig,
     ) -> Vec<CollisionHit> {
         let mut hits = Vec::new();
+        let clearance = config.clearance();
 
         for joint in chain.joints().iter().skip(1) {
             let pos = joint.position;
-            let pushed = world.push_out_point(pos, margin);
+            let pushed = world.push_out_point(pos, clearance);
 
             if (pushed - pos).length_squared() > 0.0001 {
                 if let Some((idx, _)) = world.closest_obstacle(pos) {
@@ -134,14 +213,35 @@ impl CollisionResponse {
 
                     hits.push(CollisionHit {
                         original: pos,
-                        pushed: pushed,
+                        pushed,
                         surface_point,
                         normal,
+                        bone: None,
                     });
                 }
             }
         }
 
+        for (bone, pair) in chain.joints().windows(2).enumerate() {
+            let Some(contact) =
+                world.segment_penetration(pair[0].position, pair[1].position, clearance)
+            else {
+                continue;
+            };
+            let interior = contact.t > BONE_HIT_END_SLACK && contact.t < 1.0 - BONE_HIT_END_SLACK;
+            if !interior || contact.depth * contact.depth <= 0.0001 {
+                continue;
+            }
+            let obstacle = &world.obstacles()[contact.obstacle];
+            hits.push(CollisionHit {
+                original: contact.point,
+                pushed: contact.point + contact.normal * contact.depth,
+                surface_point: obstacle.closest_surface_point(contact.point),
+                normal: contact.normal,
+                bone: Some(bone),
+            });
+        }
+
         hits
     }
 }
@@ -157,4 +257,6 @@ pub struct CollisionHit {
     pub surface_point: Vec3,
     /// Surface normal at hit point
     pub normal: Vec3,
+    /// Bone the hit lies along, or `None` for a hit on a joint
+    pub bone: Option<usize>,
 }
\ No newline at end of file
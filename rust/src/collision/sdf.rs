@@ -0,0 +1,278 @@
+use glam::Vec3;
+
+use super::obstacle::{AabbObstacle, Obstacle, ObstacleShape, SphereObstacle};
+use super::raycast::{Ray, RayHit};
+use crate::math::Transform;
+
+/// Offset of the samples taken to estimate the gradient.
+const GRADIENT_STEP: f32 = 1e-4;
+/// How close to the surface a march or projection has to get to stop.
+const SURFACE_EPSILON: f32 = 1e-4;
+const MAX_MARCH_STEPS: usize = 128;
+/// Gradient steps taken when projecting a point onto a level set.
+const PROJECTION_STEPS: usize = 8;
+
+/// A signed distance function built by combining obstacles with CSG operations.
+///
+/// Leaves are any [`Obstacle`], read through [`Obstacle::signed_distance`], so the
+/// primitives already in the crate can be cut, merged and rounded into new shapes.
+/// The operations keep the distance a lower bound on the true one, which is what
+/// sphere tracing in `ray_intersect` needs. `push_out` and the surface normal follow
+/// the gradient.
+///
+/// ```rust,ignore
+/// // An L-shaped wall with a round hole through its long side.
+/// let wall = SdfNode::cuboid(Vec3::new(0.0, 1.0, 0.0), Vec3::new(2.0, 1.0, 0.1))
+///     .union(SdfNode::cuboid(Vec3::new(1.9, 1.0, 1.0), Vec3::new(0.1, 1.0, 1.0)))
+///     .subtract(SdfNode::sphere(Vec3::new(-0.5, 1.0, 0.0), 0.4))
+///     .rounded(0.02);
+/// world.add(wall);
+/// ```
+#[derive(Debug, Clone)]
+pub enum SdfNode {
+    Shape(Box<dyn Obstacle>),
+    Union(Box<SdfNode>, Box<SdfNode>),
+    Intersection(Box<SdfNode>, Box<SdfNode>),
+    /// The first node with the second cut out of it.
+    Subtraction(Box<SdfNode>, Box<SdfNode>),
+    /// Union with the seam blended over a distance of `k`.
+    SmoothUnion {
+        a: Box<SdfNode>,
+        b: Box<SdfNode>,
+        k: f32,
+    },
+    /// `child` placed by `transform`. Distances are exact for uniform scale; otherwise
+    /// the smallest axis scale is used, which underestimates them.
+    Transform {
+        transform: Transform,
+        child: Box<SdfNode>,
+    },
+    /// `child` grown by `radius`, which rounds its edges.
+    Round {
+        radius: f32,
+        child: Box<SdfNode>,
+    },
+}
+
+impl SdfNode {
+    pub fn shape(obstacle: impl Obstacle + 'static) -> Self {
+        Self::Shape(Box::new(obstacle))
+    }
+
+    pub fn sphere(center: Vec3, radius: f32) -> Self {
+        Self::shape(SphereObstacle::new(center, radius))
+    }
+
+    pub fn cuboid(center: Vec3, half_extents: Vec3) -> Self {
+        Self::shape(AabbObstacle::from_center_half_extents(center, half_extents))
+    }
+
+    pub fn union(self, other: Self) -> Self {
+        Self::Union(Box::new(self), Box::new(other))
+    }
+
+    pub fn intersect(self, other: Self) -> Self {
+        Self::Intersection(Box::new(self), Box::new(other))
+    }
+
+    pub fn subtract(self, other: Self) -> Self {
+        Self::Subtraction(Box::new(self), Box::new(other))
+    }
+
+    pub fn smooth_union(self, other: Self, k: f32) -> Self {
+        Self::SmoothUnion {
+            a: Box::new(self),
+            b: Box::new(other),
+            k,
+        }
+    }
+
+    pub fn transformed(self, transform: Transform) -> Self {
+        Self::Transform {
+            transform,
+            child: Box::new(self),
+        }
+    }
+
+    pub fn rounded(self, radius: f32) -> Self {
+        Self::Round {
+            radius,
+            child: Box::new(self),
+        }
+    }
+
+    /// Normalised gradient of the distance, from four samples around `point`.
+    pub fn gradient(&self, point: Vec3) -> Vec3 {
+        [
+            Vec3::new(1.0, -1.0, -1.0),
+            Vec3::new(-1.0, -1.0, 1.0),
+            Vec3::new(-1.0, 1.0, -1.0),
+            Vec3::new(1.0, 1.0, 1.0),
+        ]
+        .into_iter()
+        .map(|k| k * self.signed_distance(point + k * GRADIENT_STEP))
+        .sum::<Vec3>()
+        .normalize_or_zero()
+    }
+
+    /// Moves `point` along the gradient until its distance is `level`.
+    fn project(&self, point: Vec3, level: f32) -> Vec3 {
+        let mut result = point;
+        for _ in 0..PROJECTION_STEPS {
+            let error = self.signed_distance(result) - level;
+            if error.abs() < SURFACE_EPSILON {
+                break;
+            }
+            let gradient = self.gradient(result);
+            if gradient == Vec3::ZERO {
+                break;
+            }
+            result -= gradient * error;
+        }
+        result
+    }
+}
+
+impl Obstacle for SdfNode {
+    fn contains_point(&self, point: Vec3) -> bool {
+        self.signed_distance(point) <= 0.0
+    }
+
+    fn signed_distance(&self, point: Vec3) -> f32 {
+        match self {
+            Self::Shape(obstacle) => obstacle.signed_distance(point),
+            Self::Union(a, b) => a.signed_distance(point).min(b.signed_distance(point)),
+            Self::Intersection(a, b) => a.signed_distance(point).max(b.signed_distance(point)),
+            Self::Subtraction(a, b) => a.signed_distance(point).max(-b.signed_distance(point)),
+            Self::SmoothUnion { a, b, k } => {
+                let (da, db) = (a.signed_distance(point), b.signed_distance(point));
+                if *k <= 0.0 {
+                    return da.min(db);
+                }
+                let h = (0.5 + 0.5 * (db - da) / k).clamp(0.0, 1.0);
+                db + (da - db) * h - k * h * (1.0 - h)
+            }
+            Self::Transform { transform, child } => {
+                child.signed_distance(transform.inverse_transform_point(point))
+                    * transform.scale.abs().min_element()
+            }
+            Self::Round { radius, child } => child.signed_distance(point) - radius,
+        }
+    }
+
+    fn closest_surface_point(&self, point: Vec3) -> Vec3 {
+        self.project(point, 0.0)
+    }
+
+    fn surface_normal(&self, point: Vec3) -> Vec3 {
+        self.gradient(point)
+    }
+
+    /// Sphere traces along the ray. Rays that only graze the surface may run out of
+    /// steps and report a miss.
+    fn ray_intersect(&self, ray: &Ray) -> Option<RayHit> {
+        let speed = ray.direction.length();
+        let mut t = ray.t_min;
+        for _ in 0..MAX_MARCH_STEPS {
+            if t > ray.t_max {
+                return None;
+            }
+            let point = ray.at(t);
+            // Stepping by the absolute distance finds the exit when starting inside.
+            let distance = self.signed_distance(point).abs();
+            if distance < SURFACE_EPSILON {
+                return Some(RayHit {
+                    t,
+                    point,
+                    normal: self.gradient(point),
+                });
+            }
+            t += distance / speed;
+        }
+        None
+    }
+
+    fn push_out(&self, point: Vec3, margin: f32) -> Vec3 {
+        self.project(point, margin)
+    }
+
+    fn clone_box(&self) -> Box<dyn Obstacle> {
+        Box::new(self.clone())
+    }
+
+    fn center(&self) -> Vec3 {
+        match self {
+            Self::Shape(obstacle) => obstacle.center(),
+            Self::Union(a, b) | Self::Intersection(a, b) | Self::SmoothUnion { a, b, .. } => {
+                (a.center() + b.center()) * 0.5
+            }
+            Self::Subtraction(a, _) => a.center(),
+            Self::Transform { transform, child } => transform.transform_point(child.center()),
+            Self::Round { child, .. } => child.center(),
+        }
+    }
+
+    /// Draws the leaves, including the ones that are cut away.
+    fn render_shape(&self) -> ObstacleShape {
+        match self {
+            Self::Shape(obstacle) => obstacle.render_shape(),
+            Self::Union(a, b)
+            | Self::Intersection(a, b)
+            | Self::Subtraction(a, b)
+            | Self::SmoothUnion { a, b, .. } => {
+                ObstacleShape::Group(vec![a.render_shape(), b.render_shape()])
+            }
+            Self::Transform { transform, child } => child.render_shape().transformed(transform),
+            Self::Round { child, .. } => child.render_shape(),
+        }
+    }
+
+    /// Only reported where the distance stays at least the distance to the box, so a
+    /// transform with non-uniform scale makes the node unbounded.
+    fn bounds(&self) -> Option<(Vec3, Vec3)> {
+        let pad = |(min, max): (Vec3, Vec3), margin: f32| {
+            (min - Vec3::splat(margin), max + Vec3::splat(margin))
+        };
+        let union = |(a_min, a_max): (Vec3, Vec3), (b_min, b_max): (Vec3, Vec3)| {
+            (a_min.min(b_min), a_max.max(b_max))
+        };
+
+        match self {
+            Self::Shape(obstacle) => obstacle.bounds(),
+            Self::Union(a, b) => Some(union(a.bounds()?, b.bounds()?)),
+            // The blend bulges out by at most a quarter of `k`.
+            Self::SmoothUnion { a, b, k } => {
+                Some(pad(union(a.bounds()?, b.bounds()?), k.max(0.0) * 0.25))
+            }
+            // Either side's box bounds the intersection; the distance to the overlap of
+            // the two boxes can exceed the node's distance, so it can't be used.
+            Self::Intersection(a, b) => match (a.bounds(), b.bounds()) {
+                (Some(x), Some(y)) => {
+                    let volume = |(min, max): (Vec3, Vec3)| (max - min).element_product();
+                    Some(if volume(x) <= volume(y) { x } else { y })
+                }
+                (x, y) => x.or(y),
+            },
+            Self::Subtraction(a, _) => a.bounds(),
+            Self::Transform { transform, child } => {
+                let scale = transform.scale.abs();
+                if scale.max_element() - scale.min_element() > 1e-6 * scale.max_element() {
+                    return None;
+                }
+                let (min, max) = child.bounds()?;
+                let corners = (0..8).map(|i| {
+                    let corner = Vec3::new(
+                        if i & 1 == 0 { min.x } else { max.x },
+                        if i & 2 == 0 { min.y } else { max.y },
+                        if i & 4 == 0 { min.z } else { max.z },
+                    );
+                    transform.transform_point(corner)
+                });
+                corners.fold(None, |acc: Option<(Vec3, Vec3)>, p| {
+                    Some(acc.map_or((p, p), |(lo, hi)| (lo.min(p), hi.max(p))))
+                })
+            }
+            Self::Round { radius, child } => Some(pad(child.bounds()?, radius.max(0.0))),
+        }
+    }
+}
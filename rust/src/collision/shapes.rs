@@ -0,0 +1,786 @@
+use glam::{Mat3, Quat, Vec3};
+
+use super::obstacle::{AabbObstacle, Obstacle, ObstacleShape};
+use super::raycast::{Ray, RayHit};
+use crate::math::Transform;
+
+/// Parameter in `[0, 1]` of the point on `a`–`b` closest to `point`.
+fn segment_param(point: Vec3, a: Vec3, b: Vec3) -> f32 {
+    let ab = b - a;
+    let length_squared = ab.length_squared();
+    if length_squared < 1e-12 {
+        return 0.0;
+    }
+    ((point - a).dot(ab) / length_squared).clamp(0.0, 1.0)
+}
+
+/// Unit vector perpendicular to `axis`, or `Vec3::Y` when `axis` is degenerate.
+fn perpendicular(axis: Vec3) -> Vec3 {
+    axis.try_normalize()
+        .map_or(Vec3::Y, |axis| axis.any_orthonormal_vector())
+}
+
+/// Moves `point` to `margin` outside the surface: away from the closest surface point
+/// when outside, along the surface normal when inside.
+fn push_from_surface(obstacle: &dyn Obstacle, point: Vec3, margin: f32) -> Vec3 {
+    let surface = obstacle.closest_surface_point(point);
+    let away = if obstacle.signed_distance(point) > 0.0 {
+        (point - surface).normalize_or_zero()
+    } else {
+        Vec3::ZERO
+    };
+    let outward = if away == Vec3::ZERO {
+        obstacle.surface_normal(surface)
+    } else {
+        away
+    };
+    surface + outward * margin
+}
+
+/// Picks the hit from the interval `[enter, exit]` a ray spends inside a convex
+/// obstacle: the entry, or the exit when the ray starts inside.
+fn interval_hit(obstacle: &dyn Obstacle, ray: &Ray, enter: f32, exit: f32) -> Option<RayHit> {
+    if enter > exit || exit < ray.t_min {
+        return None;
+    }
+    let t = if enter > ray.t_min { enter } else { exit };
+    if t > ray.t_max {
+        return None;
+    }
+    let point = ray.at(t);
+    Some(RayHit {
+        t,
+        point,
+        normal: obstacle.surface_normal(point),
+    })
+}
+
+/// Ray parameters where the ray is inside the sphere.
+fn sphere_interval(ray: &Ray, center: Vec3, radius: f32) -> Option<(f32, f32)> {
+    let oc = ray.origin - center;
+    let a = ray.direction.length_squared();
+    let b = oc.dot(ray.direction);
+    let c = oc.length_squared() - radius * radius;
+    let discriminant = b * b - a * c;
+    if discriminant < 0.0 || a < 1e-12 {
+        return None;
+    }
+    let sqrt_d = discriminant.sqrt();
+    Some(((-b - sqrt_d) / a, (-b + sqrt_d) / a))
+}
+
+/// Ray parameters where the ray is inside the infinite cylinder around the line
+/// through `base` along the unit `axis`.
+fn cylinder_interval(ray: &Ray, base: Vec3, axis: Vec3, radius: f32) -> Option<(f32, f32)> {
+    let oc = ray.origin - base;
+    let o = oc - axis * oc.dot(axis);
+    let d = ray.direction - axis * ray.direction.dot(axis);
+    let a = d.length_squared();
+    let c = o.length_squared() - radius * radius;
+    if a < 1e-12 {
+        return (c <= 0.0).then_some((f32::NEG_INFINITY, f32::INFINITY));
+    }
+    let b = o.dot(d);
+    let discriminant = b * b - a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let sqrt_d = discriminant.sqrt();
+    Some(((-b - sqrt_d) / a, (-b + sqrt_d) / a))
+}
+
+/// Ray parameters where the ray's coordinate along the unit `axis`, measured from
+/// `base`, lies in `[lo, hi]`.
+fn slab_interval(ray: &Ray, base: Vec3, axis: Vec3, lo: f32, hi: f32) -> Option<(f32, f32)> {
+    let o = (ray.origin - base).dot(axis);
+    let d = ray.direction.dot(axis);
+    if d.abs() < 1e-12 {
+        return (o >= lo && o <= hi).then_some((f32::NEG_INFINITY, f32::INFINITY));
+    }
+    let (t0, t1) = ((lo - o) / d, (hi - o) / d);
+    Some((t0.min(t1), t0.max(t1)))
+}
+
+/// A segment from `start` to `end` thickened by `radius`.
+#[derive(Debug, Clone, Copy)]
+pub struct CapsuleObstacle {
+    pub start: Vec3,
+    pub end: Vec3,
+    pub radius: f32,
+}
+
+impl CapsuleObstacle {
+    pub fn new(start: Vec3, end: Vec3, radius: f32) -> Self {
+        Self { start, end, radius }
+    }
+
+    fn axis_point(&self, point: Vec3) -> Vec3 {
+        self.start
+            .lerp(self.end, segment_param(point, self.start, self.end))
+    }
+
+    fn outward(&self, point: Vec3) -> Vec3 {
+        let dir = (point - self.axis_point(point)).normalize_or_zero();
+        if dir == Vec3::ZERO {
+            return perpendicular(self.end - self.start);
+        }
+        dir
+    }
+}
+
+impl Obstacle for CapsuleObstacle {
+    fn contains_point(&self, point: Vec3) -> bool {
+        self.signed_distance(point) <= 0.0
+    }
+
+    fn signed_distance(&self, point: Vec3) -> f32 {
+        (point - self.axis_point(point)).length() - self.radius
+    }
+
+    fn closest_surface_point(&self, point: Vec3) -> Vec3 {
+        self.axis_point(point) + self.outward(point) * self.radius
+    }
+
+    fn surface_normal(&self, point: Vec3) -> Vec3 {
+        self.outward(point)
+    }
+
+    fn ray_intersect(&self, ray: &Ray) -> Option<RayHit> {
+        let axis = self.end - self.start;
+        let length = axis.length();
+        let body = axis.try_normalize().and_then(|axis| {
+            let (c0, c1) = cylinder_interval(ray, self.start, axis, self.radius)?;
+            let (s0, s1) = slab_interval(ray, self.start, axis, 0.0, length)?;
+            Some((c0.max(s0), c1.min(s1))).filter(|(enter, exit)| enter <= exit)
+        });
+
+        // The capsule is convex, so the pieces the ray passes through join into one
+        // interval.
+        let (enter, exit) = [
+            sphere_interval(ray, self.start, self.radius),
+            sphere_interval(ray, self.end, self.radius),
+            body,
+        ]
+        .into_iter()
+        .flatten()
+        .reduce(|(e0, x0), (e1, x1)| (e0.min(e1), x0.max(x1)))?;
+
+        interval_hit(self, ray, enter, exit)
+    }
+
+    fn push_out(&self, point: Vec3, margin: f32) -> Vec3 {
+        self.axis_point(point) + self.outward(point) * (self.radius + margin)
+    }
+
+    fn clone_box(&self) -> Box<dyn Obstacle> {
+        Box::new(*self)
+    }
+
+    fn center(&self) -> Vec3 {
+        (self.start + self.end) * 0.5
+    }
+
+    fn render_shape(&self) -> ObstacleShape {
+        ObstacleShape::Capsule {
+            start: self.start,
+            end: self.end,
+            radius: self.radius,
+        }
+    }
+
+    fn bounds(&self) -> Option<(Vec3, Vec3)> {
+        let extent = Vec3::splat(self.radius);
+        Some((
+            self.start.min(self.end) - extent,
+            self.start.max(self.end) + extent,
+        ))
+    }
+}
+
+/// A box rotated and placed by `transform`. The transform's scale multiplies
+/// `half_extents`, so distances stay in world units.
+#[derive(Debug, Clone, Copy)]
+pub struct OrientedBoxObstacle {
+    pub transform: Transform,
+    pub half_extents: Vec3,
+}
+
+impl OrientedBoxObstacle {
+    pub fn new(transform: Transform, half_extents: Vec3) -> Self {
+        Self {
+            transform,
+            half_extents,
+        }
+    }
+
+    pub fn from_center_rotation(center: Vec3, rotation: Quat, half_extents: Vec3) -> Self {
+        Self::new(
+            Transform::from_position_rotation(center, rotation),
+            half_extents,
+        )
+    }
+
+    pub fn scaled_half_extents(&self) -> Vec3 {
+        self.half_extents * self.transform.scale.abs()
+    }
+
+    /// The box in its own frame, where it is axis aligned around the origin.
+    fn local_box(&self) -> AabbObstacle {
+        AabbObstacle::from_center_half_extents(Vec3::ZERO, self.scaled_half_extents())
+    }
+
+    fn local_point(&self, point: Vec3) -> Vec3 {
+        self.transform.rotation.inverse() * (point - self.transform.position)
+    }
+
+    fn world_point(&self, point: Vec3) -> Vec3 {
+        self.transform.rotation * point + self.transform.position
+    }
+}
+
+impl Obstacle for OrientedBoxObstacle {
+    fn contains_point(&self, point: Vec3) -> bool {
+        self.local_box().contains_point(self.local_point(point))
+    }
+
+    fn signed_distance(&self, point: Vec3) -> f32 {
+        self.local_box().signed_distance(self.local_point(point))
+    }
+
+    fn closest_surface_point(&self, point: Vec3) -> Vec3 {
+        self.world_point(
+            self.local_box()
+                .closest_surface_point(self.local_point(point)),
+        )
+    }
+
+    fn surface_normal(&self, point: Vec3) -> Vec3 {
+        self.transform.rotation * self.local_box().surface_normal(self.local_point(point))
+    }
+
+    fn ray_intersect(&self, ray: &Ray) -> Option<RayHit> {
+        let local_ray = Ray {
+            origin: self.local_point(ray.origin),
+            direction: self.transform.rotation.inverse() * ray.direction,
+            ..*ray
+        };
+        let hit = self.local_box().ray_intersect(&local_ray)?;
+        Some(RayHit {
+            t: hit.t,
+            point: self.world_point(hit.point),
+            normal: self.transform.rotation * hit.normal,
+        })
+    }
+
+    fn push_out(&self, point: Vec3, margin: f32) -> Vec3 {
+        self.world_point(self.local_box().push_out(self.local_point(point), margin))
+    }
+
+    fn clone_box(&self) -> Box<dyn Obstacle> {
+        Box::new(*self)
+    }
+
+    fn center(&self) -> Vec3 {
+        self.transform.position
+    }
+
+    fn render_shape(&self) -> ObstacleShape {
+        ObstacleShape::OrientedBox {
+            center: self.transform.position,
+            rotation: self.transform.rotation,
+            half_extents: self.scaled_half_extents(),
+        }
+    }
+
+    fn bounds(&self) -> Option<(Vec3, Vec3)> {
+        let extent = Mat3::from_quat(self.transform.rotation).abs() * self.scaled_half_extents();
+        let center = self.transform.position;
+        Some((center - extent, center + extent))
+    }
+}
+
+/// A solid half-space: everything below the plane `normal · p = offset`, such as a
+/// floor.
+#[derive(Debug, Clone, Copy)]
+pub struct PlaneObstacle {
+    pub normal: Vec3,
+    pub offset: f32,
+}
+
+impl PlaneObstacle {
+    pub fn new(normal: Vec3, offset: f32) -> Self {
+        Self {
+            normal: normal.try_normalize().unwrap_or(Vec3::Y),
+            offset,
+        }
+    }
+
+    pub fn from_point_normal(point: Vec3, normal: Vec3) -> Self {
+        let normal = normal.try_normalize().unwrap_or(Vec3::Y);
+        Self {
+            normal,
+            offset: normal.dot(point),
+        }
+    }
+
+    /// A horizontal floor at `height`, solid below.
+    pub fn floor(height: f32) -> Self {
+        Self::new(Vec3::Y, height)
+    }
+}
+
+impl Obstacle for PlaneObstacle {
+    fn contains_point(&self, point: Vec3) -> bool {
+        self.signed_distance(point) <= 0.0
+    }
+
+    fn signed_distance(&self, point: Vec3) -> f32 {
+        self.normal.dot(point) - self.offset
+    }
+
+    fn closest_surface_point(&self, point: Vec3) -> Vec3 {
+        point - self.normal * self.signed_distance(point)
+    }
+
+    fn surface_normal(&self, _point: Vec3) -> Vec3 {
+        self.normal
+    }
+
+    fn ray_intersect(&self, ray: &Ray) -> Option<RayHit> {
+        let denom = self.normal.dot(ray.direction);
+        if denom.abs() < 1e-8 {
+            return None;
+        }
+        let t = -self.signed_distance(ray.origin) / denom;
+        if t <= ray.t_min || t >= ray.t_max {
+            return None;
+        }
+        Some(RayHit {
+            t,
+            point: ray.at(t),
+            normal: self.normal,
+        })
+    }
+
+    fn push_out(&self, point: Vec3, margin: f32) -> Vec3 {
+        self.closest_surface_point(point) + self.normal * margin
+    }
+
+    fn clone_box(&self) -> Box<dyn Obstacle> {
+        Box::new(*self)
+    }
+
+    /// The point of the plane closest to the origin.
+    fn center(&self) -> Vec3 {
+        self.normal * self.offset
+    }
+
+    fn render_shape(&self) -> ObstacleShape {
+        ObstacleShape::Plane {
+            normal: self.normal,
+            offset: self.offset,
+        }
+    }
+
+    fn closest_segment_param(&self, a: Vec3, b: Vec3) -> f32 {
+        if self.signed_distance(a) <= self.signed_distance(b) {
+            0.0
+        } else {
+            1.0
+        }
+    }
+}
+
+/// A capped cylinder around `axis` through `center`, reaching `half_height` either
+/// side of it.
+#[derive(Debug, Clone, Copy)]
+pub struct CylinderObstacle {
+    pub center: Vec3,
+    pub axis: Vec3,
+    pub radius: f32,
+    pub half_height: f32,
+}
+
+impl CylinderObstacle {
+    pub fn new(center: Vec3, axis: Vec3, radius: f32, half_height: f32) -> Self {
+        Self {
+            center,
+            axis: axis.try_normalize().unwrap_or(Vec3::Y),
+            radius,
+            half_height,
+        }
+    }
+
+    pub fn from_endpoints(start: Vec3, end: Vec3, radius: f32) -> Self {
+        Self::new(
+            (start + end) * 0.5,
+            end - start,
+            radius,
+            (end - start).length() * 0.5,
+        )
+    }
+
+    /// Splits `point` into its offset from the axis and its height along it.
+    fn split(&self, point: Vec3) -> (Vec3, f32) {
+        let p = point - self.center;
+        let height = p.dot(self.axis);
+        (p - self.axis * height, height)
+    }
+
+    fn radial_dir(&self, radial: Vec3) -> Vec3 {
+        radial
+            .try_normalize()
+            .unwrap_or_else(|| perpendicular(self.axis))
+    }
+}
+
+impl Obstacle for CylinderObstacle {
+    fn contains_point(&self, point: Vec3) -> bool {
+        self.signed_distance(point) <= 0.0
+    }
+
+    fn signed_distance(&self, point: Vec3) -> f32 {
+        let (radial, height) = self.split(point);
+        let dr = radial.length() - self.radius;
+        let dh = height.abs() - self.half_height;
+        dr.max(dh).min(0.0) + Vec3::new(dr.max(0.0), dh.max(0.0), 0.0).length()
+    }
+
+    fn closest_surface_point(&self, point: Vec3) -> Vec3 {
+        let (radial, height) = self.split(point);
+        let distance = radial.length();
+        let dir = self.radial_dir(radial);
+        let dr = distance - self.radius;
+        let dh = height.abs() - self.half_height;
+
+        if dr > 0.0 || dh > 0.0 {
+            let height = height.clamp(-self.half_height, self.half_height);
+            self.center + self.axis * height + dir * distance.min(self.radius)
+        } else if dr > dh {
+            self.center + self.axis * height + dir * self.radius
+        } else {
+            self.center + self.axis * self.half_height * height.signum() + radial
+        }
+    }
+
+    fn surface_normal(&self, point: Vec3) -> Vec3 {
+        let (radial, height) = self.split(point);
+        let dr = radial.length() - self.radius;
+        let dh = height.abs() - self.half_height;
+        if dr > dh {
+            self.radial_dir(radial)
+        } else {
+            self.axis * height.signum()
+        }
+    }
+
+    fn ray_intersect(&self, ray: &Ray) -> Option<RayHit> {
+        let (c0, c1) = cylinder_interval(ray, self.center, self.axis, self.radius)?;
+        let (s0, s1) = slab_interval(
+            ray,
+            self.center,
+            self.axis,
+            -self.half_height,
+            self.half_height,
+        )?;
+        interval_hit(self, ray, c0.max(s0), c1.min(s1))
+    }
+
+    fn push_out(&self, point: Vec3, margin: f32) -> Vec3 {
+        push_from_surface(self, point, margin)
+    }
+
+    fn clone_box(&self) -> Box<dyn Obstacle> {
+        Box::new(*self)
+    }
+
+    fn center(&self) -> Vec3 {
+        self.center
+    }
+
+    fn render_shape(&self) -> ObstacleShape {
+        ObstacleShape::Cylinder {
+            center: self.center,
+            axis: self.axis,
+            radius: self.radius,
+            half_height: self.half_height,
+        }
+    }
+
+    fn bounds(&self) -> Option<(Vec3, Vec3)> {
+        // The end discs reach `radius * sin` of the angle between the axis and each
+        // world axis, on top of the axis' own reach.
+        let disc = (Vec3::ONE - self.axis * self.axis)
+            .max(Vec3::ZERO)
+            .map(f32::sqrt)
+            * self.radius;
+        let extent = self.axis.abs() * self.half_height + disc;
+        Some((self.center - extent, self.center + extent))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct HullFace {
+    normal: Vec3,
+    offset: f32,
+    /// Indices into the hull's vertices, counter-clockwise seen from outside.
+    vertices: Vec<usize>,
+}
+
+impl HullFace {
+    fn distance(&self, point: Vec3) -> f32 {
+        self.normal.dot(point) - self.offset
+    }
+
+    /// Closest point on the face polygon to `point`.
+    fn closest_point(&self, vertices: &[Vec3], point: Vec3) -> Vec3 {
+        let projected = point - self.normal * self.distance(point);
+        let edges = || {
+            let ring = &self.vertices;
+            ring.iter()
+                .zip(ring.iter().cycle().skip(1))
+                .map(|(&a, &b)| (vertices[a], vertices[b]))
+        };
+
+        let inside = edges().all(|(a, b)| (b - a).cross(projected - a).dot(self.normal) >= 0.0);
+        if inside {
+            return projected;
+        }
+        edges()
+            .map(|(a, b)| a.lerp(b, segment_param(projected, a, b)))
+            .min_by(|x, y| {
+                x.distance_squared(point)
+                    .total_cmp(&y.distance_squared(point))
+            })
+            .unwrap_or(projected)
+    }
+}
+
+/// The convex hull of a set of points.
+///
+/// The hull is found by brute force when it is built, which is meant for the few
+/// dozen points of a hand-made collider rather than a full mesh.
+#[derive(Debug, Clone)]
+pub struct ConvexHullObstacle {
+    vertices: Vec<Vec3>,
+    faces: Vec<HullFace>,
+    edges: Vec<[usize; 2]>,
+    center: Vec3,
+}
+
+impl ConvexHullObstacle {
+    /// Builds the hull of `points`, or `None` if they don't enclose any volume.
+    pub fn from_points(points: &[Vec3]) -> Option<Self> {
+        if points.len() < 4 {
+            return None;
+        }
+        let extent = points
+            .iter()
+            .map(|p| p.distance(points[0]))
+            .fold(0.0, f32::max);
+        let epsilon = (extent * 1e-5).max(1e-6);
+
+        let mut unique: Vec<Vec3> = Vec::new();
+        for &point in points {
+            if unique.iter().all(|u| u.distance(point) > epsilon) {
+                unique.push(point);
+            }
+        }
+
+        // A triangle spans a face of the hull when every point lies on or below it.
+        let mut planes: Vec<(Vec3, f32)> = Vec::new();
+        for i in 0..unique.len() {
+            for j in i + 1..unique.len() {
+                for k in j + 1..unique.len() {
+                    let Some(normal) = (unique[j] - unique[i])
+                        .cross(unique[k] - unique[i])
+                        .try_normalize()
+                    else {
+                        continue;
+                    };
+                    let offset = normal.dot(unique[i]);
+                    let above = unique.iter().any(|p| normal.dot(*p) - offset > epsilon);
+                    let below = unique.iter().any(|p| normal.dot(*p) - offset < -epsilon);
+                    let (normal, offset) = match (above, below) {
+                        (true, false) => (-normal, -offset),
+                        (false, true) => (normal, offset),
+                        _ => continue,
+                    };
+                    let known = planes
+                        .iter()
+                        .any(|(n, o)| n.dot(normal) > 1.0 - 1e-5 && (o - offset).abs() <= epsilon);
+                    if !known {
+                        planes.push((normal, offset));
+                    }
+                }
+            }
+        }
+        if planes.len() < 4 {
+            return None;
+        }
+
+        let on_plane = |p: Vec3, (n, o): (Vec3, f32)| (n.dot(p) - o).abs() <= epsilon;
+        let vertices: Vec<Vec3> = unique
+            .into_iter()
+            .filter(|p| planes.iter().filter(|plane| on_plane(*p, **plane)).count() >= 3)
+            .collect();
+
+        let faces: Vec<HullFace> = planes
+            .iter()
+            .map(|&(normal, offset)| {
+                let mut ring: Vec<usize> = (0..vertices.len())
+                    .filter(|&v| on_plane(vertices[v], (normal, offset)))
+                    .collect();
+                let centroid =
+                    ring.iter().map(|&v| vertices[v]).sum::<Vec3>() / ring.len().max(1) as f32;
+                let u = normal.any_orthonormal_vector();
+                let v = normal.cross(u);
+                let angle = |i: usize| {
+                    let d = vertices[i] - centroid;
+                    d.dot(v).atan2(d.dot(u))
+                };
+                ring.sort_by(|a, b| angle(*a).total_cmp(&angle(*b)));
+                HullFace {
+                    normal,
+                    offset,
+                    vertices: ring,
+                }
+            })
+            .collect();
+
+        let mut edges: Vec<[usize; 2]> = Vec::new();
+        for face in &faces {
+            let ring = &face.vertices;
+            for (&a, &b) in ring.iter().zip(ring.iter().cycle().skip(1)) {
+                let edge = [a.min(b), a.max(b)];
+                if !edges.contains(&edge) {
+                    edges.push(edge);
+                }
+            }
+        }
+
+        let center = vertices.iter().sum::<Vec3>() / vertices.len() as f32;
+        Some(Self {
+            vertices,
+            faces,
+            edges,
+            center,
+        })
+    }
+
+    /// Corners of the hull; input points inside it or on its faces are dropped.
+    pub fn vertices(&self) -> &[Vec3] {
+        &self.vertices
+    }
+
+    /// Pairs of indices into [`Self::vertices`].
+    pub fn edges(&self) -> &[[usize; 2]] {
+        &self.edges
+    }
+
+    pub fn face_count(&self) -> usize {
+        self.faces.len()
+    }
+
+    /// The face whose plane `point` is furthest above, with that distance.
+    fn nearest_face(&self, point: Vec3) -> (&HullFace, f32) {
+        self.faces
+            .iter()
+            .map(|face| (face, face.distance(point)))
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .expect("a hull has at least four faces")
+    }
+
+    /// Closest point on the boundary for a point outside the hull. Only faces the point
+    /// is above can hold it.
+    fn closest_outside(&self, point: Vec3) -> Vec3 {
+        self.faces
+            .iter()
+            .filter(|face| face.distance(point) > 0.0)
+            .map(|face| face.closest_point(&self.vertices, point))
+            .min_by(|x, y| {
+                x.distance_squared(point)
+                    .total_cmp(&y.distance_squared(point))
+            })
+            .unwrap_or(point)
+    }
+}
+
+impl Obstacle for ConvexHullObstacle {
+    fn contains_point(&self, point: Vec3) -> bool {
+        self.faces.iter().all(|face| face.distance(point) <= 0.0)
+    }
+
+    fn signed_distance(&self, point: Vec3) -> f32 {
+        let (_, distance) = self.nearest_face(point);
+        if distance <= 0.0 {
+            return distance;
+        }
+        point.distance(self.closest_outside(point))
+    }
+
+    fn closest_surface_point(&self, point: Vec3) -> Vec3 {
+        let (face, distance) = self.nearest_face(point);
+        if distance <= 0.0 {
+            return point - face.normal * distance;
+        }
+        self.closest_outside(point)
+    }
+
+    fn surface_normal(&self, point: Vec3) -> Vec3 {
+        self.nearest_face(point).0.normal
+    }
+
+    fn ray_intersect(&self, ray: &Ray) -> Option<RayHit> {
+        let mut enter = f32::NEG_INFINITY;
+        let mut exit = f32::INFINITY;
+        for face in &self.faces {
+            let denom = face.normal.dot(ray.direction);
+            let distance = face.distance(ray.origin);
+            if denom.abs() < 1e-12 {
+                if distance > 0.0 {
+                    return None;
+                }
+                continue;
+            }
+            let t = -distance / denom;
+            if denom < 0.0 {
+                enter = enter.max(t);
+            } else {
+                exit = exit.min(t);
+            }
+        }
+        interval_hit(self, ray, enter, exit)
+    }
+
+    fn push_out(&self, point: Vec3, margin: f32) -> Vec3 {
+        push_from_surface(self, point, margin)
+    }
+
+    fn clone_box(&self) -> Box<dyn Obstacle> {
+        Box::new(self.clone())
+    }
+
+    fn center(&self) -> Vec3 {
+        self.center
+    }
+
+    fn render_shape(&self) -> ObstacleShape {
+        ObstacleShape::ConvexHull {
+            vertices: self.vertices.clone(),
+            edges: self.edges.clone(),
+        }
+    }
+
+    fn bounds(&self) -> Option<(Vec3, Vec3)> {
+        let min = self
+            .vertices
+            .iter()
+            .fold(Vec3::INFINITY, |acc, v| acc.min(*v));
+        let max = self
+            .vertices
+            .iter()
+            .fold(Vec3::NEG_INFINITY, |acc, v| acc.max(*v));
+        Some((min, max))
+    }
+}
@@ -1,49 +1,214 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+
 use glam::Vec3;
 
-use super::obstacle::{AabbObstacle, Obstacle, SphereObstacle};
+use super::bvh::{Bounds, Bvh};
+use super::obstacle::{AabbObstacle, Obstacle, SegmentContact, SphereObstacle};
 use super::raycast::{Ray, RayHit};
+use super::shapes::{CapsuleObstacle, CylinderObstacle, OrientedBoxObstacle, PlaneObstacle};
+use crate::math::Transform;
+
+/// Below this many obstacles queries check them all, which beats walking a tree.
+const BROADPHASE_MIN_OBSTACLES: usize = 8;
+/// Grows obstacle bounds so surface hits found to within a tolerance stay inside.
+const BOUNDS_PADDING: f32 = 1e-3;
+
+/// Source of [`ObstacleWorld::stamp`]s, shared by every world so no two obstacle states
+/// get the same one.
+static NEXT_STAMP: AtomicU64 = AtomicU64::new(0);
+
+fn next_stamp() -> u64 {
+    NEXT_STAMP.fetch_add(1, Ordering::Relaxed)
+}
+
+/// BVH over the obstacles with trusted [`Obstacle::bounds`]; the rest are always checked.
+#[derive(Debug, Clone)]
+struct Broadphase {
+    bvh: Bvh,
+    /// World index of each BVH item, in increasing order.
+    bounded: Vec<usize>,
+    bounds: Vec<Bounds>,
+    unbounded: Vec<usize>,
+}
 
-#[derive(Default, Clone)]
+impl Broadphase {
+    fn build(obstacles: &[Box<dyn Obstacle>]) -> Self {
+        let mut bounded = Vec::new();
+        let mut bounds = Vec::new();
+        let mut unbounded = Vec::new();
+        for (i, obstacle) in obstacles.iter().enumerate() {
+            match obstacle_bounds(obstacle.as_ref()) {
+                Some(b) => {
+                    bounded.push(i);
+                    bounds.push(b);
+                }
+                None => unbounded.push(i),
+            }
+        }
+        Self {
+            bvh: Bvh::build(&bounds),
+            bounded,
+            bounds,
+            unbounded,
+        }
+    }
+
+    /// World indices of the obstacles whose bounds come within `radius` of `point`,
+    /// in increasing order.
+    fn near(&self, point: Vec3, radius: f32) -> Vec<usize> {
+        let mut candidates = self.unbounded.clone();
+        self.bvh.visit_near(point, radius.max(0.0), |item| {
+            candidates.push(self.bounded[item])
+        });
+        candidates.sort_unstable();
+        candidates
+    }
+}
+
+/// Padded bounds of `obstacle`, or `None` if it is unbounded or its bounds can't be
+/// trusted.
+///
+/// Culling is only safe if the distance outside the box is never below the distance
+/// to it. That can't be proven for an arbitrary obstacle, so it is sampled at the
+/// corners, edge midpoints and face centres of the padded box and of one twice its
+/// size. An obstacle whose distance there is below the distance to the box is checked
+/// on every query like an unbounded one, which keeps results the same as the linear
+/// path.
+fn obstacle_bounds(obstacle: &dyn Obstacle) -> Option<Bounds> {
+    let (min, max) = obstacle.bounds()?;
+    let bounds = Bounds { min, max }.padded(BOUNDS_PADDING);
+    let center = bounds.center();
+    let half = bounds.max - center;
+    let trusted = (0..27)
+        .map(|i| Vec3::new((i % 3) as f32, (i / 3 % 3) as f32, (i / 9) as f32) - Vec3::ONE)
+        .filter(|&k| k != Vec3::ZERO)
+        .flat_map(|k| [center + k * half, center + k * half * 2.0])
+        .all(|p| obstacle.signed_distance(p) >= bounds.distance(p));
+    trusted.then_some(bounds)
+}
+
+/// The obstacles a chain collides with.
+///
+/// Queries go through a bounding volume hierarchy once there are more than a handful
+/// of obstacles. It is built on the first query after obstacles are added and refit
+/// by [`ObstacleWorld::replace`]; results are the same as checking every obstacle.
+/// Obstacles without [`Obstacle::bounds`], or whose distances break them, are always
+/// checked.
+#[derive(Clone)]
 pub struct ObstacleWorld {
     obstacles: Vec<Box<dyn Obstacle>>,
+    /// See [`Self::stamp`].
+    stamps: Vec<u64>,
+    broadphase: OnceLock<Broadphase>,
+    broadphase_enabled: bool,
+}
+
+impl Default for ObstacleWorld {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl ObstacleWorld {
     pub fn new() -> Self {
         Self {
             obstacles: Vec::new(),
+            stamps: Vec::new(),
+            broadphase: OnceLock::new(),
+            broadphase_enabled: true,
         }
     }
 
     pub fn add<T: Obstacle + 'static>(&mut self, obstacle: T) {
         self.obstacles.push(Box::new(obstacle));
+        self.stamps.push(next_stamp());
+        self.broadphase = OnceLock::new();
     }
 
     pub fn add_sphere(&mut self, center: Vec3, radius: f32) {
-        self.obstacles
-            .push(Box::new(SphereObstacle::new(center, radius)));
+        self.add(SphereObstacle::new(center, radius));
     }
 
     pub fn add_box(&mut self, center: Vec3, half_extents: Vec3) {
-        self.obstacles
-            .push(Box::new(AabbObstacle::from_center_half_extents(
-                center,
-                half_extents,
-            )));
+        self.add(AabbObstacle::from_center_half_extents(center, half_extents));
     }
 
     pub fn add_aabb(&mut self, min: Vec3, max: Vec3) {
-        self.obstacles.push(Box::new(AabbObstacle::new(min, max)));
+        self.add(AabbObstacle::new(min, max));
+    }
+
+    pub fn add_capsule(&mut self, start: Vec3, end: Vec3, radius: f32) {
+        self.add(CapsuleObstacle::new(start, end, radius));
+    }
+
+    pub fn add_oriented_box(&mut self, transform: Transform, half_extents: Vec3) {
+        self.add(OrientedBoxObstacle::new(transform, half_extents));
+    }
+
+    /// Adds a solid half-space below the plane `normal · p = offset`.
+    pub fn add_plane(&mut self, normal: Vec3, offset: f32) {
+        self.add(PlaneObstacle::new(normal, offset));
+    }
+
+    pub fn add_cylinder(&mut self, center: Vec3, axis: Vec3, radius: f32, half_height: f32) {
+        self.add(CylinderObstacle::new(center, axis, radius, half_height));
+    }
+
+    /// Swaps the obstacle at `index` for `obstacle`, refitting the broadphase rather
+    /// than rebuilding it. Panics if `index` is out of range.
+    pub fn replace<T: Obstacle + 'static>(&mut self, index: usize, obstacle: T) {
+        let bounds = obstacle_bounds(&obstacle);
+        self.obstacles[index] = Box::new(obstacle);
+        self.stamps[index] = next_stamp();
+
+        let Some(broadphase) = self.broadphase.get_mut() else {
+            return;
+        };
+        match (broadphase.bounded.binary_search(&index), bounds) {
+            (Ok(item), Some(bounds)) => {
+                broadphase.bounds[item] = bounds;
+                broadphase.bvh.refit(&broadphase.bounds);
+            }
+            (Err(_), None) => {}
+            _ => self.broadphase = OnceLock::new(),
+        }
     }
 
     pub fn clear(&mut self) {
         self.obstacles.clear();
+        self.stamps.clear();
+        self.broadphase = OnceLock::new();
+    }
+
+    /// Turns the broadphase off to check every obstacle on each query, e.g. to compare
+    /// the two.
+    pub fn set_broadphase_enabled(&mut self, enabled: bool) {
+        self.broadphase_enabled = enabled;
+    }
+
+    pub fn broadphase_enabled(&self) -> bool {
+        self.broadphase_enabled
+    }
+
+    fn broadphase(&self) -> Option<&Broadphase> {
+        (self.broadphase_enabled && self.obstacles.len() >= BROADPHASE_MIN_OBSTACLES).then(|| {
+            self.broadphase
+                .get_or_init(|| Broadphase::build(&self.obstacles))
+        })
     }
 
     pub fn obstacles(&self) -> &[Box<dyn Obstacle>] {
         &self.obstacles
     }
 
+    /// Identifies the obstacle at `index` as it is now. Adding or replacing an obstacle
+    /// gives it a stamp no obstacle had before, so anything derived from it can be cached
+    /// under the stamp. Panics if `index` is out of range.
+    pub(crate) fn stamp(&self, index: usize) -> u64 {
+        self.stamps[index]
+    }
+
     pub fn obstacle_count(&self) -> usize {
         self.obstacles.len()
     }
@@ -53,21 +218,70 @@ impl ObstacleWorld {
     }
 
     pub fn point_inside_any(&self, point: Vec3) -> bool {
-        self.obstacles.iter().any(|o| o.contains_point(point))
+        match self.broadphase() {
+            Some(broadphase) => broadphase
+                .near(point, 0.0)
+                .into_iter()
+                .any(|i| self.obstacles[i].contains_point(point)),
+            None => self.obstacles.iter().any(|o| o.contains_point(point)),
+        }
     }
 
     pub fn closest_obstacle(&self, point: Vec3) -> Option<(usize, f32)> {
-        self.obstacles
-            .iter()
-            .enumerate()
-            .map(|(i, o)| (i, o.signed_distance(point)))
-            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+        let Some(broadphase) = self.broadphase() else {
+            return self
+                .obstacles
+                .iter()
+                .enumerate()
+                .map(|(i, o)| (i, o.signed_distance(point)))
+                .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+        };
+
+        let mut closest = broadphase
+            .bvh
+            .closest(point, |item| {
+                self.obstacles[broadphase.bounded[item]].signed_distance(point)
+            })
+            .map(|(item, d)| (broadphase.bounded[item], d));
+        for &i in &broadphase.unbounded {
+            let d = self.obstacles[i].signed_distance(point);
+            if closest.is_none_or(|(j, best)| d < best || (d == best && i < j)) {
+                closest = Some((i, d));
+            }
+        }
+        closest
     }
 
     pub fn raycast(&self, ray: &Ray) -> Option<(usize, RayHit)> {
+        let Some(broadphase) = self.broadphase() else {
+            return Self::nearest_hit(self.obstacles.iter().enumerate(), ray);
+        };
+
+        let bounded = broadphase
+            .bvh
+            .raycast(ray, |item| {
+                self.obstacles[broadphase.bounded[item]]
+                    .ray_intersect(ray)
+                    .map(|hit| hit.t)
+            })
+            .map(|(item, _)| broadphase.bounded[item]);
+        let mut candidates: Vec<usize> = broadphase
+            .unbounded
+            .iter()
+            .copied()
+            .chain(bounded)
+            .collect();
+        candidates.sort_unstable();
+        Self::nearest_hit(candidates.into_iter().map(|i| (i, &self.obstacles[i])), ray)
+    }
+
+    fn nearest_hit<'a>(
+        obstacles: impl Iterator<Item = (usize, &'a Box<dyn Obstacle>)>,
+        ray: &Ray,
+    ) -> Option<(usize, RayHit)> {
         let mut closest: Option<(usize, RayHit)> = None;
 
-        for (i, obstacle) in self.obstacles.iter().enumerate() {
+        for (i, obstacle) in obstacles {
             if let Some(hit) = obstacle.ray_intersect(ray) {
                 match &closest {
                     None => closest = Some((i, hit)),
@@ -82,15 +296,68 @@ impl ObstacleWorld {
         closest
     }
 
+    /// Deepest penetration of the segment `a`–`b`, thickened into a capsule of `radius`,
+    /// into any obstacle.
+    pub fn segment_penetration(&self, a: Vec3, b: Vec3, radius: f32) -> Option<SegmentContact> {
+        let contact = |i: usize| {
+            let obstacle = &self.obstacles[i];
+            let t = obstacle.closest_segment_param(a, b);
+            let point = a.lerp(b, t);
+            let depth = radius - obstacle.signed_distance(point);
+            if depth <= 0.0 {
+                return None;
+            }
+            let normal = obstacle
+                .surface_normal(obstacle.closest_surface_point(point))
+                .normalize_or_zero();
+            Some(SegmentContact {
+                obstacle: i,
+                t,
+                point,
+                normal,
+                depth,
+            })
+        };
+
+        let candidates = match self.broadphase() {
+            Some(broadphase) => broadphase.near((a + b) * 0.5, a.distance(b) * 0.5 + radius),
+            None => (0..self.obstacles.len()).collect(),
+        };
+        candidates
+            .into_iter()
+            .filter_map(contact)
+            .max_by(|x, y| x.depth.total_cmp(&y.depth))
+    }
+
     pub fn push_out_point(&self, point: Vec3, margin: f32) -> Vec3 {
         let mut result = point;
 
         for _ in 0..4 {
             let mut pushed = false;
-            for obstacle in &self.obstacles {
-                if obstacle.signed_distance(result) < margin {
-                    result = obstacle.push_out(result, margin);
-                    pushed = true;
+            match self.broadphase() {
+                // Obstacles whose bounds are out of reach can't need a push, so only
+                // the ones near the current point are visited, in the same order.
+                Some(broadphase) => {
+                    let mut next = 0;
+                    'pass: loop {
+                        for i in broadphase.near(result, margin) {
+                            if i >= next && self.obstacles[i].signed_distance(result) < margin {
+                                result = self.obstacles[i].push_out(result, margin);
+                                pushed = true;
+                                next = i + 1;
+                                continue 'pass;
+                            }
+                        }
+                        break;
+                    }
+                }
+                None => {
+                    for obstacle in &self.obstacles {
+                        if obstacle.signed_distance(result) < margin {
+                            result = obstacle.push_out(result, margin);
+                            pushed = true;
+                        }
+                    }
                 }
             }
             if !pushed {
@@ -108,4 +375,177 @@ impl std::fmt::Debug for ObstacleWorld {
             .field("obstacle_count", &self.obstacles.len())
             .finish()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Points spread through and around the test worlds, and rays from them in a
+    /// spread of directions.
+    fn samples() -> (Vec<Vec3>, Vec<Ray>) {
+        let steps = (-5..=5).map(|i| i as f32 * 1.3 + 0.17);
+        let points: Vec<Vec3> = steps
+            .clone()
+            .flat_map(|x| {
+                let steps = steps.clone();
+                steps.flat_map(move |y| [Vec3::new(x, y, 0.4 * x - 0.3 * y), Vec3::new(y, x, 1.1)])
+            })
+            .collect();
+        let directions = [
+            Vec3::X,
+            Vec3::NEG_Y,
+            Vec3::new(1.0, 1.0, 0.0).normalize(),
+            Vec3::new(-0.3, 0.2, 0.9).normalize(),
+            Vec3::new(0.5, -0.8, -0.3).normalize(),
+        ];
+        let rays = points
+            .iter()
+            .zip(directions.iter().cycle())
+            .map(|(&origin, &direction)| Ray::new(origin, direction))
+            .collect();
+        (points, rays)
+    }
+
+    /// Adds enough bounded obstacles of each kind for queries to go through the
+    /// broadphase.
+    fn add_bounded(world: &mut ObstacleWorld) {
+        for i in 0..24 {
+            let angle = i as f32 * 0.7;
+            let center = Vec3::new(
+                angle.cos() * 5.0,
+                (i as f32 - 12.0) * 0.5,
+                angle.sin() * 5.0,
+            );
+            let size = 0.4 + (i % 4) as f32 * 0.3;
+            match i % 4 {
+                0 => world.add_sphere(center, size),
+                1 => world.add_box(center, Vec3::new(size, size * 0.5, size)),
+                2 => world.add_capsule(center, center + Vec3::new(1.0, 0.5, 0.0), size * 0.5),
+                _ => world.add_cylinder(center, Vec3::Y, size, size * 0.5),
+            }
+        }
+    }
+
+    fn bounded_world() -> ObstacleWorld {
+        let mut world = ObstacleWorld::new();
+        add_bounded(&mut world);
+        world
+    }
+
+    /// Asserts every query gives the same answer with the broadphase as without it.
+    fn assert_matches_linear(world: &ObstacleWorld) {
+        let mut linear = world.clone();
+        linear.set_broadphase_enabled(false);
+        let (points, rays) = samples();
+
+        for &point in &points {
+            assert_eq!(
+                world.point_inside_any(point),
+                linear.point_inside_any(point),
+                "point_inside_any at {point}"
+            );
+            assert_eq!(
+                world.closest_obstacle(point),
+                linear.closest_obstacle(point),
+                "closest_obstacle at {point}"
+            );
+            assert_eq!(
+                world.push_out_point(point, 0.3),
+                linear.push_out_point(point, 0.3),
+                "push_out_point at {point}"
+            );
+            let end = point + Vec3::new(0.8, -0.6, 0.4);
+            let contact = |w: &ObstacleWorld| {
+                w.segment_penetration(point, end, 0.2)
+                    .map(|c| (c.obstacle, c.t, c.point, c.normal, c.depth))
+            };
+            assert_eq!(
+                contact(world),
+                contact(&linear),
+                "segment_penetration at {point}"
+            );
+        }
+        for ray in &rays {
+            let hit = |w: &ObstacleWorld| {
+                w.raycast(ray)
+                    .map(|(i, hit)| (i, hit.t, hit.point, hit.normal))
+            };
+            assert_eq!(hit(world), hit(&linear), "raycast from {}", ray.origin);
+        }
+    }
+
+    #[test]
+    fn broadphase_matches_linear_with_planes() {
+        let mut world = bounded_world();
+        world.add_plane(Vec3::Y, -4.0);
+        world.add_plane(Vec3::new(1.0, 0.0, 1.0).normalize(), 6.0);
+        assert!(world.broadphase().is_some());
+        assert_eq!(world.broadphase().unwrap().unbounded, [24, 25]);
+        assert_matches_linear(&world);
+
+        // Unbounded obstacles before the bounded ones keep their indices.
+        let mut world = ObstacleWorld::new();
+        world.add_plane(Vec3::NEG_X, 3.0);
+        add_bounded(&mut world);
+        assert_matches_linear(&world);
+    }
+
+    #[test]
+    fn broadphase_matches_linear_with_only_planes() {
+        let mut world = ObstacleWorld::new();
+        for i in 0..BROADPHASE_MIN_OBSTACLES + 2 {
+            let angle = i as f32 * 0.9;
+            world.add_plane(Vec3::new(angle.cos(), 0.5, angle.sin()).normalize(), -6.0);
+        }
+        assert!(world.broadphase().is_some());
+        assert_matches_linear(&world);
+    }
+
+    #[test]
+    fn broadphase_matches_linear_after_replace() {
+        let mut world = bounded_world();
+        world.add_plane(Vec3::Y, -4.0);
+        // Builds the tree so the replacements below refit it.
+        assert_matches_linear(&world);
+
+        // Bounded for bounded refits in place, and the moved sphere is found there.
+        world.replace(3, SphereObstacle::new(Vec3::new(0.0, 9.0, 0.0), 1.0));
+        assert!(world.broadphase.get().is_some());
+        assert_eq!(
+            world.closest_obstacle(Vec3::new(0.0, 9.0, 0.0)).unwrap().0,
+            3
+        );
+        assert_matches_linear(&world);
+
+        world.replace(
+            0,
+            AabbObstacle::from_center_half_extents(Vec3::ZERO, Vec3::splat(2.0)),
+        );
+        assert_matches_linear(&world);
+
+        // Swapping between bounded and unbounded obstacles rebuilds instead.
+        world.replace(5, PlaneObstacle::new(Vec3::X, 4.0));
+        assert_matches_linear(&world);
+        world.replace(24, SphereObstacle::new(Vec3::new(-3.0, -2.0, 1.0), 0.8));
+        assert_matches_linear(&world);
+    }
+
+    #[test]
+    fn broadphase_matches_linear_when_empty() {
+        let mut world = ObstacleWorld::new();
+        assert_matches_linear(&world);
+        assert_eq!(world.closest_obstacle(Vec3::ZERO), None);
+        assert!(world.raycast(&Ray::new(Vec3::ZERO, Vec3::X)).is_none());
+        assert_eq!(world.push_out_point(Vec3::ONE, 0.5), Vec3::ONE);
+
+        // A world emptied after its tree was built doesn't keep answering from it.
+        world = bounded_world();
+        assert_matches_linear(&world);
+        world.clear();
+        assert!(world.is_empty());
+        assert_matches_linear(&world);
+        assert_eq!(world.closest_obstacle(Vec3::ZERO), None);
+        assert!(!world.point_inside_any(Vec3::ZERO));
+    }
+}
@@ -0,0 +1,122 @@
+//! Blending between an animation-driven (FK) pose and an IK solve.
+
+use super::chain::Chain;
+use super::solver::{FabrikSolver, SolveResult};
+use crate::dynamics::{SecondOrderDynamics, SpringPreset};
+use crate::math::Transform;
+use glam::Vec3;
+
+/// Per-chain IK/FK blend weight: 0 keeps the FK pose, 1 keeps the IK solve.
+///
+/// Poses are blended joint by joint on their local frames, so rotations are slerped
+/// and bone lengths are kept rather than averaging raw positions. With a transition
+/// set, [`Self::update`] eases the weight towards [`Self::target_weight`] through
+/// [`SecondOrderDynamics`], so switching IK on and off doesn't pop.
+pub struct IkFkBlend {
+    weight: f32,
+    target_weight: f32,
+    transition: Option<SecondOrderDynamics<f32>>,
+}
+
+impl IkFkBlend {
+    pub fn new(weight: f32) -> Self {
+        let weight = weight.clamp(0.0, 1.0);
+        Self {
+            weight,
+            target_weight: weight,
+            transition: None,
+        }
+    }
+
+    /// Eases weight changes with a second order system of frequency `f`, damping `z`
+    /// and response `r`; see [`SecondOrderDynamics::new`].
+    pub fn with_transition(mut self, f: f32, z: f32, r: f32) -> Self {
+        self.transition = Some(SecondOrderDynamics::new(f, z, r, self.weight));
+        self
+    }
+
+    pub fn with_preset(mut self, preset: SpringPreset) -> Self {
+        self.transition = Some(SecondOrderDynamics::from_preset(preset, self.weight));
+        self
+    }
+
+    /// The weight currently applied by [`Self::apply`].
+    pub fn weight(&self) -> f32 {
+        self.weight
+    }
+
+    /// The weight [`Self::update`] is moving towards.
+    pub fn target_weight(&self) -> f32 {
+        self.target_weight
+    }
+
+    /// Jumps straight to `weight`, skipping any transition.
+    pub fn set_weight(&mut self, weight: f32) {
+        let weight = weight.clamp(0.0, 1.0);
+        self.weight = weight;
+        self.target_weight = weight;
+        if let Some(transition) = &mut self.transition {
+            transition.reset(weight);
+        }
+    }
+
+    pub fn set_target_weight(&mut self, weight: f32) {
+        self.target_weight = weight.clamp(0.0, 1.0);
+    }
+
+    /// Fades IK fully in or out.
+    pub fn set_ik_enabled(&mut self, enabled: bool) {
+        self.set_target_weight(if enabled { 1.0 } else { 0.0 });
+    }
+
+    /// Advances the transition by `dt` seconds and returns the new weight. Without a
+    /// transition the weight snaps to the target.
+    pub fn update(&mut self, dt: f32) -> f32 {
+        self.weight = match &mut self.transition {
+            Some(transition) => transition.update(self.target_weight, dt).clamp(0.0, 1.0),
+            None => self.target_weight,
+        };
+        self.weight
+    }
+
+    /// Poses `chain` from `fk_pose`, solves towards `target` with [`FabrikSolver`], and
+    /// blends the two. See [`Self::apply`].
+    pub fn solve(&self, chain: &mut Chain, fk_pose: &[Transform], target: Vec3) -> SolveResult {
+        self.apply(chain, fk_pose, |chain| FabrikSolver::solve(chain, target))
+    }
+
+    /// Poses `chain` from `fk_pose` with [`Chain::apply_fk`], runs `solve` on it, and
+    /// leaves the chain in the blend of the two poses.
+    ///
+    /// Starting the solve from the FK pose keeps the IK pose close to the animation.
+    /// The returned result describes the IK pose before blending.
+    pub fn apply(
+        &self,
+        chain: &mut Chain,
+        fk_pose: &[Transform],
+        solve: impl FnOnce(&mut Chain) -> SolveResult,
+    ) -> SolveResult {
+        chain.apply_fk(fk_pose);
+        let fk: Vec<Transform> = chain.local_frames().collect();
+        let result = solve(chain);
+        if self.weight < 1.0 {
+            let ik: Vec<Transform> = chain.local_frames().collect();
+            chain.apply_fk(&Self::blend(&fk, &ik, self.weight));
+        }
+        result
+    }
+
+    /// Blends two sets of local frames, slerping rotations and lerping translations.
+    pub fn blend(fk: &[Transform], ik: &[Transform], weight: f32) -> Vec<Transform> {
+        fk.iter()
+            .zip(ik)
+            .map(|(fk, ik)| fk.lerp(ik, weight))
+            .collect()
+    }
+}
+
+impl Default for IkFkBlend {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
@@ -0,0 +1,112 @@
+use super::chain::Chain;
+use super::solver::{Clamps, FabrikSolver, Goal, IkSolver, SolveResult, SolveTrace};
+use crate::collision::{CollisionConfig, ObstacleWorld};
+use glam::{Quat, Vec3};
+
+/// Cyclic Coordinate Descent.
+///
+/// Each iteration walks from the joint before the end effector back to the base and
+/// rotates everything past the current joint so the end effector swings onto the line
+/// from that joint to the target, within that joint's constraint. The pole target and
+/// collisions are applied once per sweep.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CcdSolver;
+
+impl CcdSolver {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn solve_inner(
+        chain: &mut Chain,
+        target: Vec3,
+        collision: Option<(&ObstacleWorld, &CollisionConfig)>,
+    ) -> SolveResult {
+        let n = chain.joints.len();
+        if !target.is_finite() {
+            return SolveResult::rejected();
+        }
+        if n < 2 {
+            return SolveResult::trivial();
+        }
+
+        let base = chain.joints[0].position;
+        chain.fit_stretch(base, target);
+        let base_frame = chain.base_frame();
+        let goal = Goal::position(target);
+        let tolerance = chain.tolerance();
+        let mut trace = SolveTrace::new(chain);
+
+        for _ in 0..chain.max_iterations {
+            Self::sweep(chain, target, base_frame, 0, &mut trace.clamps);
+            FabrikSolver::settle(chain, goal, base, base_frame, collision, &mut trace);
+
+            let distance = (chain.joints[n - 1].position - target).length();
+            trace.record(distance);
+            if distance <= tolerance || trace.is_stalled() {
+                break;
+            }
+        }
+
+        chain.update_frames();
+        trace.finish(chain, goal, base)
+    }
+}
+
+impl CcdSolver {
+    /// One sweep from the joint before the end effector back to joint `first`, turning
+    /// each sub-chain about its joint towards `target`. Each joint is clamped against
+    /// its parent's frame as updated so far, and `chain`'s world frames are kept in step.
+    pub(crate) fn sweep(
+        chain: &mut Chain,
+        target: Vec3,
+        base_frame: Quat,
+        first: usize,
+        clamps: &mut Clamps,
+    ) {
+        let n = chain.joints.len();
+        for i in (first..n - 1).rev() {
+            let pivot = chain.joints[i].position;
+            let end = chain.joints[n - 1].position;
+            let (Some(from), Some(to)) = (
+                (end - pivot).try_normalize(),
+                (target - pivot).try_normalize(),
+            ) else {
+                continue;
+            };
+
+            // Rotating the whole sub-chain keeps every joint past `i` in its own
+            // limits, so only joint i needs constraining.
+            let current = chain.joints[i].world_frame.rotation;
+            let parent_frame = match i {
+                0 => base_frame,
+                _ => chain.joints[i - 1].world_frame.rotation,
+            };
+            let desired = Quat::from_rotation_arc(from, to) * current;
+            let allowed = FabrikSolver::constrain(&chain.joints[i], desired, parent_frame, clamps);
+            let rotation = allowed * current.inverse();
+
+            chain.joints[i].world_frame.rotation = allowed;
+            for joint in &mut chain.joints[i + 1..] {
+                joint.position = pivot + rotation * (joint.position - pivot);
+                joint.world_frame.rotation = rotation * joint.world_frame.rotation;
+            }
+        }
+    }
+}
+
+impl IkSolver for CcdSolver {
+    fn solve(&self, chain: &mut Chain, target: Vec3) -> SolveResult {
+        Self::solve_inner(chain, target, None)
+    }
+
+    fn solve_with_collision(
+        &self,
+        chain: &mut Chain,
+        target: Vec3,
+        world: &ObstacleWorld,
+        config: &CollisionConfig,
+    ) -> SolveResult {
+        Self::solve_inner(chain, target, Some((world, config)))
+    }
+}
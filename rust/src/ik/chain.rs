@@ -1,12 +1,108 @@
 use super::constraint::Constraint;
-use super::joint::Joint;
-use glam::Vec3;
+use super::error::ChainError;
+use super::frame;
+use super::joint::{Joint, JointTarget};
+use crate::math::Transform;
+use glam::{EulerRot, Quat, Vec3};
+
+/// Bones shorter than this are rejected by [`ChainBuilder::try_build`].
+const MIN_BONE_LENGTH: f32 = 1e-6;
+
+/// Whether and how far the base joint may move while a chain is solved, e.g. a hip that
+/// shifts when a foot reaches too far. Non-pinned modes start every solve from the
+/// chain's root anchor and only move the base for the part of the target out of reach.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum RootMode {
+    /// The base stays where it is.
+    #[default]
+    Pinned,
+    /// The base may move anywhere.
+    Free,
+    /// The base may move up to this distance from the anchor.
+    Radius(f32),
+    /// The base may slide along the plane through the anchor with this normal.
+    Plane(Vec3),
+}
+
+impl RootMode {
+    /// How far the base moves away from `anchor` so that `target` comes within `reach`,
+    /// scaled by `weight` and limited by the mode. Targets already in reach leave the
+    /// base at the anchor.
+    pub fn offset(self, anchor: Vec3, target: Vec3, reach: f32, weight: f32) -> Vec3 {
+        let to_target = target - anchor;
+        let needed = match self {
+            RootMode::Pinned => return Vec3::ZERO,
+            RootMode::Free | RootMode::Radius(_) => {
+                let distance = to_target.length();
+                if distance <= reach {
+                    return Vec3::ZERO;
+                }
+                to_target * ((distance - reach) / distance)
+            }
+            RootMode::Plane(normal) => {
+                let normal = normal.normalize_or_zero();
+                let height = to_target.dot(normal);
+                let along = to_target - normal * height;
+                let distance = along.length();
+                let slack = (reach * reach - height * height).max(0.0).sqrt();
+                if distance <= slack {
+                    return Vec3::ZERO;
+                }
+                along * ((distance - slack) / distance)
+            }
+        };
+
+        let offset = needed * weight.clamp(0.0, 1.0);
+        match self {
+            RootMode::Radius(radius) => offset.clamp_length_max(radius.max(0.0)),
+            _ => offset,
+        }
+    }
+}
+
+/// Shortest and longest a bone may become when the solver stretches or squashes it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LengthRange {
+    pub min: f32,
+    pub max: f32,
+}
+
+impl LengthRange {
+    pub fn new(min: f32, max: f32) -> Self {
+        let min = min.max(0.0);
+        Self {
+            min,
+            max: max.max(min),
+        }
+    }
+
+    /// Range given as scale factors of `length`.
+    pub fn scaled(length: f32, min_scale: f32, max_scale: f32) -> Self {
+        Self::new(length * min_scale, length * max_scale)
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct Chain {
     pub(crate) joints: Vec<Joint>,
     pub(crate) bone_lengths: Vec<f32>,
+    pub(crate) bone_scales: Vec<f32>,
+    pub(crate) length_ranges: Vec<Option<LengthRange>>,
+    pub(crate) stretch_stiffness: f32,
+    pub(crate) rest_base: Vec3,
+    pub(crate) rest_rotations: Vec<Quat>,
+    pub(crate) rest_bias: f32,
+    pub(crate) record_error_history: bool,
+    pub(crate) base_direction: Vec3,
+    pub(crate) up_vector: Vec3,
+    pub(crate) pole_target: Option<Vec3>,
+    pub(crate) root_mode: RootMode,
+    pub(crate) root_anchor: Vec3,
+    pub(crate) root_weight: f32,
     pub(crate) tolerance: f32,
+    pub(crate) relative_tolerance: bool,
+    pub(crate) stall_threshold: f32,
+    pub(crate) orientation_tolerance: f32,
     pub(crate) max_iterations: u32,
 }
 
@@ -23,16 +119,170 @@ impl Chain {
         &mut self.joints
     }
 
+    /// Unstretched length of every bone.
     pub fn bone_lengths(&self) -> &[f32] {
         &self.bone_lengths
     }
 
+    /// Current length of `bone`, including any stretch from the last solve.
+    ///
+    /// # Panics
+    /// Panics if `bone` is out of bounds.
+    pub fn bone_length(&self, bone: usize) -> f32 {
+        self.bone_lengths[bone] * self.bone_scales[bone]
+    }
+
+    /// Stretch factor of every bone from the last solve; 1 means unstretched.
+    pub fn bone_scales(&self) -> &[f32] {
+        &self.bone_scales
+    }
+
+    pub fn length_ranges(&self) -> &[Option<LengthRange>] {
+        &self.length_ranges
+    }
+
+    /// Lets bone `bone` stretch or squash within `range` while solving, or keeps it
+    /// rigid with `None`.
+    ///
+    /// # Panics
+    /// Panics if `bone` is out of bounds.
+    pub fn set_length_range(&mut self, bone: usize, range: Option<LengthRange>) {
+        self.length_ranges[bone] = range;
+    }
+
+    /// How strongly stretchy bones resist changing length, from 0 (they change as much
+    /// as their ranges allow to reach the target) to 1 (rigid).
+    pub fn stretch_stiffness(&self) -> f32 {
+        self.stretch_stiffness
+    }
+
+    pub fn set_stretch_stiffness(&mut self, stiffness: f32) {
+        self.stretch_stiffness = stiffness.clamp(0.0, 1.0);
+    }
+
+    /// Reference direction used by the base joint's constraint.
+    pub fn base_direction(&self) -> Vec3 {
+        self.base_direction
+    }
+
+    /// Frame the base joint's constraint is expressed in: +Y along
+    /// [`Self::base_direction`], rolled towards [`Self::up_vector`].
+    pub fn base_frame(&self) -> Quat {
+        frame::from_direction(self.base_direction, self.up_vector)
+    }
+
+    /// Current length of the chain, including any stretch from the last solve.
     pub fn total_length(&self) -> f32 {
+        (0..self.bone_lengths.len()).map(|i| self.bone_length(i)).sum()
+    }
+
+    pub fn unstretched_length(&self) -> f32 {
         self.bone_lengths.iter().sum()
     }
 
+    /// Picks the bone scales for a solve from `base` towards `target`.
+    ///
+    /// Bones start from their unstretched length. When the target is further than the
+    /// chain reaches, bones with a length range grow towards their maximum; when it is
+    /// closer than the chain can fold, they shrink towards their minimum. Anywhere in
+    /// between the chain bends instead. The change is shared out in proportion to each
+    /// bone's room to move and reduced by the stretch stiffness.
+    pub(crate) fn fit_stretch(&mut self, base: Vec3, target: Vec3) {
+        self.bone_scales.clear();
+        self.bone_scales.resize(self.bone_lengths.len(), 1.0);
+        if self.length_ranges.iter().all(Option::is_none) {
+            return;
+        }
+
+        let rest = self.unstretched_length();
+        let distance = (target - base).length();
+        // The longest bone folded back over the others sets how close the chain reaches.
+        let longest = self.bone_lengths.iter().copied().fold(0.0, f32::max);
+        let fold_reach = (2.0 * longest - rest).max(0.0);
+        let stretching = distance > rest;
+        if !stretching && distance >= fold_reach {
+            return;
+        }
+        let room = |length: f32, range: &LengthRange| match stretching {
+            true => (range.max - length).max(0.0),
+            false => (length - range.min).max(0.0),
+        };
+
+        let capacity: f32 = self
+            .bone_lengths
+            .iter()
+            .zip(&self.length_ranges)
+            .filter_map(|(&length, range)| Some(room(length, range.as_ref()?)))
+            .sum();
+        if capacity <= 0.0 {
+            return;
+        }
+
+        // Squashing every bone by the same factor scales the fold reach with it.
+        let change = match stretching {
+            true => distance - rest,
+            false => rest * (1.0 - distance / fold_reach),
+        };
+        let wanted = change * (1.0 - self.stretch_stiffness);
+        let share = (wanted / capacity).min(1.0);
+        let sign = if stretching { 1.0 } else { -1.0 };
+
+        for ((scale, &length), range) in self
+            .bone_scales
+            .iter_mut()
+            .zip(&self.bone_lengths)
+            .zip(&self.length_ranges)
+        {
+            if let Some(range) = range {
+                if length > 0.0 {
+                    *scale = (length + sign * room(length, range) * share) / length;
+                }
+            }
+        }
+    }
+
+    /// Largest end effector distance that counts as converged. With a relative
+    /// tolerance this is that fraction of the current [`Self::total_length`].
     pub fn tolerance(&self) -> f32 {
-        self.tolerance
+        if self.relative_tolerance {
+            self.tolerance * self.total_length()
+        } else {
+            self.tolerance
+        }
+    }
+
+    pub fn is_tolerance_relative(&self) -> bool {
+        self.relative_tolerance
+    }
+
+    /// Sets an absolute tolerance in world units.
+    pub fn set_tolerance(&mut self, tolerance: f32) {
+        self.tolerance = tolerance;
+        self.relative_tolerance = false;
+    }
+
+    /// Sets the tolerance as a fraction of the chain's total length, so the same value
+    /// suits a finger and a crane.
+    pub fn set_relative_tolerance(&mut self, fraction: f32) {
+        self.tolerance = fraction;
+        self.relative_tolerance = true;
+    }
+
+    /// An iteration that brings the end effector closer by less than this fraction of
+    /// [`Self::tolerance`] counts as stalled, and two in a row end the solve early. 0
+    /// disables the early exit.
+    pub fn stall_threshold(&self) -> f32 {
+        self.stall_threshold
+    }
+
+    pub fn set_stall_threshold(&mut self, threshold: f32) {
+        self.stall_threshold = threshold.max(0.0);
+    }
+
+    /// Largest end effector orientation error, in radians, that still counts as converged
+    /// for pose targets.
+    pub fn orientation_tolerance(&self) -> f32 {
+        self.orientation_tolerance
     }
 
     pub fn max_iterations(&self) -> u32 {
@@ -54,11 +304,519 @@ impl Chain {
     pub fn positions(&self) -> impl Iterator<Item = Vec3> + '_ {
         self.joints.iter().map(|j| j.position)
     }
+
+    /// Roll reference used when a bone frame has to be built from scratch.
+    pub fn up_vector(&self) -> Vec3 {
+        self.up_vector
+    }
+
+    pub fn set_up_vector(&mut self, up: Vec3) {
+        self.up_vector = up.normalize_or_zero();
+    }
+
+    /// Point the chain bends towards, like an elbow or knee hint.
+    pub fn pole_target(&self) -> Option<Vec3> {
+        self.pole_target
+    }
+
+    pub fn set_pole_target(&mut self, pole: Option<Vec3>) {
+        self.pole_target = pole;
+    }
+
+    pub fn root_mode(&self) -> RootMode {
+        self.root_mode
+    }
+
+    pub fn set_root_mode(&mut self, mode: RootMode) {
+        self.root_mode = mode;
+    }
+
+    /// Rest position of the base that non-pinned root modes measure from. Defaults to
+    /// where the base was built.
+    pub fn root_anchor(&self) -> Vec3 {
+        self.root_anchor
+    }
+
+    pub fn set_root_anchor(&mut self, anchor: Vec3) {
+        self.root_anchor = anchor;
+    }
+
+    /// Share of the out-of-reach distance the base absorbs in non-pinned root modes,
+    /// from 0 to 1.
+    pub fn root_weight(&self) -> f32 {
+        self.root_weight
+    }
+
+    pub fn set_root_weight(&mut self, weight: f32) {
+        self.root_weight = weight.clamp(0.0, 1.0);
+    }
+
+    /// Recomputes every joint's world and local frame from the current positions.
+    ///
+    /// The base frame is carried over from its previous orientation with a minimal
+    /// rotation, and each following bone is aligned from its parent's frame, so roll
+    /// stays continuous both along the chain and from one solve to the next. Bones whose
+    /// constraint limits twist are aligned from their own previous frame instead, so the
+    /// twist they were given survives.
+    pub fn update_frames(&mut self) {
+        self.refresh_frames(false);
+    }
+
+    /// [`Self::update_frames`], optionally keeping the last bone's own roll as set by a
+    /// pose target.
+    pub(crate) fn refresh_frames(&mut self, keep_end_roll: bool) {
+        let n = self.joints.len();
+        let up = self.up_vector;
+        let mut parent_rotation: Option<Quat> = None;
+
+        for i in 0..n {
+            let rotation = if i + 1 < n {
+                let direction = self.joints[i + 1].position - self.joints[i].position;
+                let seed = match parent_rotation {
+                    Some(_) if keep_end_roll && i + 2 == n => self.joints[i].world_frame.rotation,
+                    Some(parent) => self.joints[i].frame_seed(parent),
+                    None => self.joints[i].world_frame.rotation,
+                };
+                frame::align(seed, direction, up)
+            } else {
+                parent_rotation.unwrap_or(self.joints[i].world_frame.rotation)
+            };
+
+            let position = self.joints[i].position;
+            let local_frame = match (parent_rotation, i.checked_sub(1)) {
+                (Some(parent), Some(prev)) => {
+                    let inverse = parent.inverse();
+                    Transform::from_position_rotation(
+                        inverse * (position - self.joints[prev].position),
+                        inverse * rotation,
+                    )
+                }
+                _ => Transform::from_position_rotation(position, rotation),
+            };
+
+            let joint = &mut self.joints[i];
+            joint.world_frame = Transform::from_position_rotation(position, rotation);
+            joint.local_frame = local_frame;
+            parent_rotation = Some(rotation);
+        }
+    }
+
+    /// Attaches a mid-chain target to joint `index`, or clears it with `None`.
+    ///
+    /// # Panics
+    /// Panics if `index` is out of bounds.
+    pub fn set_joint_target(&mut self, index: usize, target: Option<JointTarget>) {
+        self.joints[index].target = target;
+    }
+
+    /// Pins joint `index` at `position`; see [`JointTarget::pinned`].
+    ///
+    /// # Panics
+    /// Panics if `index` is out of bounds.
+    pub fn pin_joint(&mut self, index: usize, position: Vec3) {
+        self.set_joint_target(index, Some(JointTarget::pinned(position)));
+    }
+
+    pub fn pinned_joints(&self) -> impl Iterator<Item = usize> + '_ {
+        self.joints
+            .iter()
+            .enumerate()
+            .filter(|(_, j)| j.is_pinned())
+            .map(|(i, _)| i)
+    }
+
+    pub fn world_frames(&self) -> impl Iterator<Item = Transform> + '_ {
+        self.joints.iter().map(|j| j.world_frame)
+    }
+
+    /// Each joint's frame relative to its parent joint's frame. The base joint's local
+    /// frame is its world frame.
+    pub fn local_frames(&self) -> impl Iterator<Item = Transform> + '_ {
+        self.joints.iter().map(|j| j.local_frame)
+    }
+
+    /// Forward kinematics: the world frames that `locals` would put the joints in,
+    /// without changing the chain.
+    ///
+    /// `locals` holds one frame per joint, laid out like [`Self::local_frames`]: each
+    /// joint's translation is its offset from the parent joint in the parent's frame,
+    /// so bones run along the parent's +Y axis. Joints past the end of `locals` keep
+    /// their current local frame.
+    pub fn evaluate_fk(&self, locals: &[Transform]) -> Vec<Transform> {
+        let mut parent: Option<Transform> = None;
+        self.joints
+            .iter()
+            .enumerate()
+            .map(|(i, joint)| {
+                let local = locals.get(i).copied().unwrap_or(joint.local_frame);
+                let local = Transform::from_position_rotation(local.position, local.rotation);
+                let world = match parent {
+                    Some(parent) => parent.mul_transform(&local),
+                    None => local,
+                };
+                parent = Some(world);
+                world
+            })
+            .collect()
+    }
+
+    /// Poses the chain with [`Self::evaluate_fk`]. Bone lengths follow the local
+    /// translations, any stretch is cleared and the root anchor moves with the base.
+    pub fn apply_fk(&mut self, locals: &[Transform]) {
+        let worlds = self.evaluate_fk(locals);
+        for (i, world) in worlds.into_iter().enumerate() {
+            let local = match i.checked_sub(1) {
+                Some(prev) => self.joints[prev].world_frame.inverse().mul_transform(&world),
+                None => world,
+            };
+            let joint = &mut self.joints[i];
+            joint.position = world.position;
+            joint.world_frame = world;
+            joint.local_frame = local;
+        }
+
+        self.bone_lengths = bone_lengths_of(&self.joints);
+        self.bone_scales.fill(1.0);
+        if let Some(base) = self.base() {
+            self.root_anchor = base;
+        }
+    }
+
+    /// Each joint's local rotation as Euler angles in radians, in the axis order of
+    /// `order`. The base joint's angles are relative to world space.
+    ///
+    /// A straight chain has zero angles on every joint past the base, so after a solve
+    /// these can be sent as joint commands and fed back through
+    /// [`Self::set_joint_angles`].
+    pub fn joint_angles(&self, order: EulerRot) -> Vec<Vec3> {
+        self.joints
+            .iter()
+            .map(|j| Vec3::from(j.local_frame.rotation.to_euler(order)))
+            .collect()
+    }
+
+    /// Sets each joint's local rotation from Euler angles in radians and re-runs forward
+    /// kinematics, keeping bone lengths. Joints past the end of `angles` keep their
+    /// current local rotation.
+    pub fn set_joint_angles(&mut self, angles: &[Vec3], order: EulerRot) {
+        let locals: Vec<Transform> = self
+            .joints
+            .iter()
+            .zip(angles)
+            .map(|(joint, angles)| {
+                Transform::from_position_rotation(
+                    joint.local_frame.position,
+                    Quat::from_euler(order, angles.x, angles.y, angles.z),
+                )
+            })
+            .collect();
+        self.apply_fk(&locals);
+    }
+
+    /// Makes the current pose the rest pose.
+    ///
+    /// The rest pose is captured when the chain is built, and again after edits that
+    /// add, remove or move joints. It is stored as bone orientations, so bone length
+    /// edits carry over to it.
+    pub fn set_rest_pose(&mut self) {
+        self.rest_base = self.base().unwrap_or(Vec3::ZERO);
+        self.rest_rotations = self.joints.iter().map(|j| j.world_frame.rotation).collect();
+    }
+
+    /// Joint positions of the rest pose at the current, unstretched bone lengths.
+    pub fn rest_positions(&self) -> Vec<Vec3> {
+        let mut position = self.rest_base;
+        let mut positions = Vec::with_capacity(self.joints.len());
+        for (i, rotation) in self.rest_rotations.iter().enumerate() {
+            positions.push(position);
+            if let Some(length) = self.bone_lengths.get(i) {
+                position += *rotation * Vec3::Y * *length;
+            }
+        }
+        positions
+    }
+
+    /// Puts every joint back in the rest pose and clears any stretch.
+    pub fn reset_to_rest(&mut self) {
+        let positions = self.rest_positions();
+        for ((joint, position), rotation) in self
+            .joints
+            .iter_mut()
+            .zip(positions)
+            .zip(&self.rest_rotations)
+        {
+            joint.position = position;
+            joint.world_frame.rotation = *rotation;
+        }
+        self.bone_scales.fill(1.0);
+        self.update_frames();
+    }
+
+    /// How strongly each solve pulls the chain towards its rest pose, from 0 (not at
+    /// all) to 1 (every solve starts from the rest pose).
+    pub fn rest_bias(&self) -> f32 {
+        self.rest_bias
+    }
+
+    pub fn set_rest_bias(&mut self, bias: f32) {
+        self.rest_bias = bias.clamp(0.0, 1.0);
+    }
+
+    /// Whether solves fill in [`SolveResult::error_history`](super::SolveResult::error_history).
+    pub fn record_error_history(&self) -> bool {
+        self.record_error_history
+    }
+
+    pub fn set_record_error_history(&mut self, record: bool) {
+        self.record_error_history = record;
+    }
+
+    /// Blends the joints `strength` of the way towards the rest pose, carried along so
+    /// its base sits on `base`.
+    pub(crate) fn blend_towards_rest(&mut self, base: Vec3, strength: f32) {
+        let offset = base - self.rest_base;
+        let rest_positions = self.rest_positions();
+        for (joint, rest) in self.joints.iter_mut().zip(rest_positions) {
+            joint.position = joint.position.lerp(rest + offset, strength);
+        }
+    }
+
+    /// Inserts `joint` at `index`, shifting later joints along. The bones on either
+    /// side of it take their lengths from the new positions, and a bone split in two
+    /// shares its length range between the halves in proportion to their lengths.
+    ///
+    /// # Panics
+    /// Panics if `index > joint_count()`.
+    pub fn insert_joint(&mut self, index: usize, mut joint: Joint) {
+        let neighbour = index.checked_sub(1).or((!self.joints.is_empty()).then_some(0));
+        if let Some(neighbour) = neighbour {
+            joint.world_frame.rotation = self.joints[neighbour].world_frame.rotation;
+        }
+        self.joints.insert(index, joint);
+        if index > 0 && index + 1 < self.joints.len() {
+            // The joint splits bone `index - 1`; each half keeps its share of the range.
+            let (before, after) = (index - 1, index);
+            let old_length = self.bone_lengths[before];
+            let scale = self.bone_scales[before];
+            let halves = [before, after].map(|bone| {
+                let measured = self.joints[bone]
+                    .position
+                    .distance(self.joints[bone + 1].position);
+                unstretched(measured, scale)
+            });
+            let range = self.length_ranges[before].filter(|_| old_length > 0.0);
+            let split = |length: f32| {
+                range.map(|range| {
+                    LengthRange::scaled(length, range.min / old_length, range.max / old_length)
+                })
+            };
+            self.length_ranges[before] = split(halves[0]);
+            self.length_ranges.insert(after, split(halves[1]));
+            self.bone_scales.insert(after, scale);
+        } else if self.joints.len() > 1 {
+            let bone = index.min(self.length_ranges.len());
+            self.length_ranges.insert(bone, None);
+            self.bone_scales.insert(bone, 1.0);
+        }
+        self.after_structure_edit(index);
+    }
+
+    /// Removes and returns joint `index`. Its neighbours are joined by a single bone as
+    /// long as the distance between them, whose length range allows the same stretch
+    /// and squash as the two bones it replaces together.
+    ///
+    /// # Panics
+    /// Panics if `index` is out of bounds.
+    pub fn remove_joint(&mut self, index: usize) -> Joint {
+        let joint = self.joints.remove(index);
+        if index > 0 && index < self.joints.len() {
+            let (before, after) = (index - 1, index);
+            let old_length = self.bone_lengths[before] + self.bone_lengths[after];
+            let stretched = self.bone_length(before) + self.bone_length(after);
+            let scale = if old_length > 0.0 { stretched / old_length } else { 1.0 };
+            let new_length = unstretched(
+                self.joints[before]
+                    .position
+                    .distance(self.joints[index].position),
+                scale,
+            );
+            let merged = merge_length_ranges(
+                [before, after].map(|bone| (self.bone_lengths[bone], self.length_ranges[bone])),
+            );
+            self.length_ranges[before] = merged.map(|(min, max)| {
+                let scale = |length: f32| if old_length > 0.0 { length / old_length } else { 1.0 };
+                LengthRange::scaled(new_length, scale(min), scale(max))
+            });
+            self.length_ranges.remove(after);
+            self.bone_scales[before] = scale;
+            self.bone_scales.remove(after);
+        } else if !self.length_ranges.is_empty() {
+            let bone = index.min(self.length_ranges.len() - 1);
+            self.length_ranges.remove(bone);
+            self.bone_scales.remove(bone);
+        }
+        self.after_structure_edit(index);
+        joint
+    }
+
+    /// Moves joint `index` to `position`, updating the lengths of the bones on either
+    /// side of it. Use this instead of writing through [`Self::joints_mut`] when the
+    /// move is meant to reshape the chain.
+    ///
+    /// # Panics
+    /// Panics if `index` is out of bounds.
+    pub fn set_joint_position(&mut self, index: usize, position: Vec3) {
+        self.joints[index].position = position;
+        self.after_structure_edit(index);
+    }
+
+    /// Sets the length of bone `bone` (between joints `bone` and `bone + 1`) by moving
+    /// every joint after it along the bone. Its length range scales with it.
+    ///
+    /// # Panics
+    /// Panics if `bone` is out of bounds.
+    pub fn set_bone_length(&mut self, bone: usize, length: f32) {
+        let length = length.max(0.0);
+        let start = self.joints[bone].position;
+        let direction = (self.joints[bone + 1].position - start)
+            .try_normalize()
+            .unwrap_or(self.joints[bone].world_frame.rotation * Vec3::Y);
+        let offset = start + direction * length - self.joints[bone + 1].position;
+
+        for joint in &mut self.joints[bone + 1..] {
+            joint.position += offset;
+        }
+        let old_length = self.bone_lengths[bone];
+        self.length_ranges[bone] = self.length_ranges[bone]
+            .filter(|_| old_length > 0.0)
+            .map(|range| {
+                let factor = length / old_length;
+                LengthRange::new(range.min * factor, range.max * factor)
+            });
+        self.bone_lengths[bone] = length;
+        self.update_frames();
+    }
+
+    /// Scales every bone by `factor` about the base joint.
+    pub fn rescale(&mut self, factor: f32) {
+        let factor = factor.max(0.0);
+        let Some(base) = self.base() else {
+            return;
+        };
+
+        for joint in &mut self.joints {
+            joint.position = base + (joint.position - base) * factor;
+        }
+        for length in &mut self.bone_lengths {
+            *length *= factor;
+        }
+        for range in self.length_ranges.iter_mut().flatten() {
+            *range = LengthRange::new(range.min * factor, range.max * factor);
+        }
+        self.update_frames();
+    }
+
+    /// Replaces the constraint on joint `index`, returning the previous one.
+    ///
+    /// # Panics
+    /// Panics if `index` is out of bounds.
+    pub fn set_joint_constraint(
+        &mut self,
+        index: usize,
+        constraint: Option<Box<dyn Constraint>>,
+    ) -> Option<Box<dyn Constraint>> {
+        std::mem::replace(&mut self.joints[index].constraint, constraint)
+    }
+
+    /// Re-measures every bone from the current joint positions, e.g. after editing
+    /// them through [`Self::joints_mut`].
+    pub fn sync_bone_lengths(&mut self) {
+        self.bone_lengths = bone_lengths_of(&self.joints);
+        self.bone_scales.fill(1.0);
+        self.update_frames();
+        self.set_rest_pose();
+    }
+
+    /// Re-measures the bones after an edit, taking out the stretch the last solve left
+    /// in them so it doesn't become their new length. Expects `bone_scales` to already
+    /// line up with the edited bones.
+    pub(crate) fn after_structure_edit(&mut self, index: usize) {
+        let measured = bone_lengths_of(&self.joints);
+        self.bone_lengths = measured
+            .iter()
+            .enumerate()
+            .map(|(bone, &length)| {
+                unstretched(length, self.bone_scales.get(bone).copied().unwrap_or(1.0))
+            })
+            .collect();
+        self.length_ranges.resize(self.bone_lengths.len(), None);
+        self.bone_scales.clear();
+        self.bone_scales.resize(self.bone_lengths.len(), 1.0);
+        if index <= 1 {
+            self.base_direction = base_direction_of(&self.joints);
+        }
+        if index == 0 {
+            if let Some(base) = self.base() {
+                self.root_anchor = base;
+            }
+        }
+        self.update_frames();
+        self.set_rest_pose();
+    }
+}
+
+/// Shortest and longest two bones can be together, or `None` if both are rigid. A rigid
+/// bone counts as a range of just its length.
+fn merge_length_ranges(bones: [(f32, Option<LengthRange>); 2]) -> Option<(f32, f32)> {
+    if bones.iter().all(|(_, range)| range.is_none()) {
+        return None;
+    }
+    Some(bones.iter().fold((0.0, 0.0), |(min, max), &(length, range)| {
+        let range = range.unwrap_or(LengthRange::new(length, length));
+        (min + range.min, max + range.max)
+    }))
+}
+
+/// Length a bone measured at `length` has without the stretch `scale`.
+fn unstretched(length: f32, scale: f32) -> f32 {
+    if scale > 0.0 {
+        length / scale
+    } else {
+        length
+    }
+}
+
+fn bone_lengths_of(joints: &[Joint]) -> Vec<f32> {
+    joints
+        .windows(2)
+        .map(|w| (w[1].position - w[0].position).length())
+        .collect()
+}
+
+fn base_direction_of(joints: &[Joint]) -> Vec3 {
+    match joints {
+        [first, second, ..] => (second.position - first.position)
+            .try_normalize()
+            .unwrap_or(Vec3::Y),
+        _ => Vec3::Y,
+    }
 }
 
 pub struct ChainBuilder {
     joints: Vec<Joint>,
+    up_vector: Vec3,
+    pole_target: Option<Vec3>,
+    root_mode: RootMode,
+    root_weight: f32,
+    stretch_limits: Option<(f32, f32)>,
+    length_ranges: Vec<(usize, LengthRange)>,
+    stretch_stiffness: f32,
+    rest_bias: f32,
+    record_error_history: bool,
     tolerance: f32,
+    relative_tolerance: bool,
+    stall_threshold: f32,
+    orientation_tolerance: f32,
     max_iterations: u32,
 }
 
@@ -66,7 +824,19 @@ impl ChainBuilder {
     pub fn new() -> Self {
         Self {
             joints: Vec::new(),
+            up_vector: Vec3::Z,
+            pole_target: None,
+            root_mode: RootMode::Pinned,
+            root_weight: 1.0,
+            stretch_limits: None,
+            length_ranges: Vec::new(),
+            stretch_stiffness: 0.0,
+            rest_bias: 0.0,
+            record_error_history: false,
             tolerance: 0.001,
+            relative_tolerance: false,
+            stall_threshold: 0.0,
+            orientation_tolerance: 0.01,
             max_iterations: 10,
         }
     }
@@ -85,8 +855,98 @@ impl ChainBuilder {
         self
     }
 
+    /// Adds a joint pinned at its own position.
+    pub fn add_pinned_joint(mut self, position: Vec3) -> Self {
+        self.joints
+            .push(Joint::new(position).with_target(JointTarget::pinned(position)));
+        self
+    }
+
+    pub fn add_joint_with_target(mut self, position: Vec3, target: JointTarget) -> Self {
+        self.joints.push(Joint::new(position).with_target(target));
+        self
+    }
+
+    /// Roll reference for bone frames. Defaults to `Vec3::Z`.
+    pub fn up_vector(mut self, up: Vec3) -> Self {
+        self.up_vector = up.normalize_or_zero();
+        self
+    }
+
+    /// Point the solved chain should bend towards. Without one the bend plane is
+    /// whatever the previous pose leads to.
+    pub fn pole_target(mut self, pole: Vec3) -> Self {
+        self.pole_target = Some(pole);
+        self
+    }
+
+    /// How the base may move while solving. Defaults to [`RootMode::Pinned`].
+    pub fn root_mode(mut self, mode: RootMode) -> Self {
+        self.root_mode = mode;
+        self
+    }
+
+    /// Share of the out-of-reach distance the base absorbs in non-pinned root modes.
+    /// Defaults to 1.
+    pub fn root_weight(mut self, weight: f32) -> Self {
+        self.root_weight = weight.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Lets every bone stretch or squash between `min_scale` and `max_scale` times its
+    /// built length. Ranges set with [`Self::length_range`] take precedence.
+    pub fn stretch_limits(mut self, min_scale: f32, max_scale: f32) -> Self {
+        self.stretch_limits = Some((min_scale, max_scale));
+        self
+    }
+
+    /// Length range of bone `bone`, the bone from joint `bone` to joint `bone + 1`.
+    pub fn length_range(mut self, bone: usize, range: LengthRange) -> Self {
+        self.length_ranges.push((bone, range));
+        self
+    }
+
+    /// See [`Chain::stretch_stiffness`]. Defaults to 0.
+    pub fn stretch_stiffness(mut self, stiffness: f32) -> Self {
+        self.stretch_stiffness = stiffness.clamp(0.0, 1.0);
+        self
+    }
+
+    /// See [`Chain::rest_bias`]. Defaults to 0.
+    pub fn rest_bias(mut self, bias: f32) -> Self {
+        self.rest_bias = bias.clamp(0.0, 1.0);
+        self
+    }
+
+    /// See [`Chain::set_record_error_history`].
+    pub fn record_error_history(mut self, record: bool) -> Self {
+        self.record_error_history = record;
+        self
+    }
+
     pub fn tolerance(mut self, tolerance: f32) -> Self {
         self.tolerance = tolerance;
+        self.relative_tolerance = false;
+        self
+    }
+
+    /// See [`Chain::set_relative_tolerance`].
+    pub fn relative_tolerance(mut self, fraction: f32) -> Self {
+        self.tolerance = fraction;
+        self.relative_tolerance = true;
+        self
+    }
+
+    /// See [`Chain::stall_threshold`]. Defaults to 0, which keeps the early exit off so
+    /// solves run until they converge or use up `max_iterations`; around 0.01 turns it on.
+    pub fn stall_threshold(mut self, threshold: f32) -> Self {
+        self.stall_threshold = threshold.max(0.0);
+        self
+    }
+
+    /// Orientation tolerance in radians for pose targets. Defaults to 0.01.
+    pub fn orientation_tolerance(mut self, tolerance: f32) -> Self {
+        self.orientation_tolerance = tolerance;
         self
     }
 
@@ -95,25 +955,110 @@ impl ChainBuilder {
         self
     }
 
-    pub fn build(self) -> Chain {
-        let bone_lengths = self.calculate_bone_lengths();
-        Chain {
+    /// Checks the chain before building it, where [`Self::build`] accepts anything.
+    ///
+    /// Rejects chains with fewer than two joints, coincident joints, non-finite joint
+    /// positions, a tolerance that isn't positive, and length ranges for bones the chain
+    /// does not have.
+    pub fn try_build(self) -> Result<Chain, ChainError> {
+        match self.joints.len() {
+            0 => return Err(ChainError::NoJoints),
+            1 => return Err(ChainError::SingleJoint),
+            _ => {}
+        }
+        if let Some(joint) = self.joints.iter().position(|j| !j.position.is_finite()) {
+            return Err(ChainError::NonFinitePosition { joint });
+        }
+        if let Some(bone) = self
+            .joints
+            .windows(2)
+            .position(|pair| pair[0].position.distance(pair[1].position) <= MIN_BONE_LENGTH)
+        {
+            return Err(ChainError::CoincidentJoints { bone });
+        }
+        if !self.tolerance.is_finite() || self.tolerance <= 0.0 {
+            return Err(ChainError::InvalidTolerance(self.tolerance));
+        }
+        let bone_count = self.joints.len() - 1;
+        if let Some(&(bone, _)) = self.length_ranges.iter().find(|(bone, _)| *bone >= bone_count) {
+            return Err(ChainError::LengthRangeOutOfBounds { bone, bone_count });
+        }
+        Ok(self.build())
+    }
+
+    /// Builds the chain without checking it; see [`Self::try_build`].
+    ///
+    /// # Panics
+    /// Panics if a [`Self::length_range`] names a bone the chain does not have.
+    pub fn build(mut self) -> Chain {
+        let bone_lengths = bone_lengths_of(&self.joints);
+        let base_direction = base_direction_of(&self.joints);
+        let root_anchor = self.joints.first().map_or(Vec3::ZERO, |j| j.position);
+        let length_ranges = self.calculate_length_ranges(&bone_lengths);
+        self.seed_frames();
+
+        let mut chain = Chain {
             joints: self.joints,
+            bone_scales: vec![1.0; bone_lengths.len()],
             bone_lengths,
+            length_ranges,
+            stretch_stiffness: self.stretch_stiffness,
+            rest_base: Vec3::ZERO,
+            rest_rotations: Vec::new(),
+            rest_bias: self.rest_bias,
+            record_error_history: self.record_error_history,
+            base_direction,
+            up_vector: self.up_vector,
+            pole_target: self.pole_target,
+            root_mode: self.root_mode,
+            root_anchor,
+            root_weight: self.root_weight,
             tolerance: self.tolerance,
+            relative_tolerance: self.relative_tolerance,
+            stall_threshold: self.stall_threshold,
+            orientation_tolerance: self.orientation_tolerance,
             max_iterations: self.max_iterations,
-        }
+        };
+        chain.update_frames();
+        chain.set_rest_pose();
+        chain
     }
 
-    fn calculate_bone_lengths(&self) -> Vec<f32> {
-        if self.joints.len() < 2 {
-            return Vec::new();
+    fn calculate_length_ranges(&self, bone_lengths: &[f32]) -> Vec<Option<LengthRange>> {
+        let mut ranges: Vec<Option<LengthRange>> = bone_lengths
+            .iter()
+            .map(|&length| {
+                let (min_scale, max_scale) = self.stretch_limits?;
+                Some(LengthRange::scaled(length, min_scale, max_scale))
+            })
+            .collect();
+
+        for &(bone, range) in &self.length_ranges {
+            assert!(
+                bone < ranges.len(),
+                "length range for bone {bone} but the chain has {} bones",
+                ranges.len()
+            );
+            ranges[bone] = Some(range);
         }
+        ranges
+    }
 
-        self.joints
-            .windows(2)
-            .map(|w| (w[1].position - w[0].position).length())
-            .collect()
+    /// Gives every joint an initial frame built from its outgoing bone and the up
+    /// vector, so the first [`Chain::update_frames`] has a sensible roll to start from.
+    fn seed_frames(&mut self) {
+        let n = self.joints.len();
+        let mut rotation = Quat::IDENTITY;
+        for i in 0..n {
+            if i + 1 < n {
+                let direction = self.joints[i + 1].position - self.joints[i].position;
+                rotation = match i {
+                    0 => frame::from_direction(direction, self.up_vector),
+                    _ => frame::align(rotation, direction, self.up_vector),
+                };
+            }
+            self.joints[i].world_frame.rotation = rotation;
+        }
     }
 }
 
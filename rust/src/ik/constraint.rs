@@ -1,8 +1,37 @@
+use super::frame;
 use glam::{Quat, Vec3};
 use std::fmt::Debug;
 
 pub trait Constraint: Send + Sync + Debug {
     fn apply(&self, direction: Vec3, reference: Vec3) -> Vec3;
+
+    /// Constrains `direction` against the parent bone's full frame, whose +Y axis is the
+    /// parent bone direction. Constraints that only care about the parent direction can
+    /// rely on the default, which forwards to [`Constraint::apply`].
+    fn apply_in_frame(&self, direction: Vec3, parent: Quat) -> Vec3 {
+        self.apply(direction, parent * Vec3::Y)
+    }
+
+    /// Constrains a bone's world `rotation` (+Y along the bone) against its parent's
+    /// world rotation and returns the allowed rotation.
+    ///
+    /// The default only limits the bone direction through [`Constraint::apply_in_frame`]
+    /// and carries the incoming twist over with the smallest correcting rotation.
+    fn apply_rotation(&self, rotation: Quat, parent: Quat) -> Quat {
+        let direction = rotation * Vec3::Y;
+        let constrained = self.apply_in_frame(direction, parent);
+        if constrained.length_squared() < 0.0001 {
+            return rotation;
+        }
+        (Quat::from_rotation_arc(direction, constrained.normalize()) * rotation).normalize()
+    }
+
+    /// Whether this constraint limits roll around the bone. Bones whose constraint does
+    /// keep their own twist between solves instead of inheriting the parent's.
+    fn constrains_twist(&self) -> bool {
+        false
+    }
+
     fn clone_box(&self) -> Box<dyn Constraint>;
 }
 
@@ -58,6 +87,291 @@ impl Constraint for BallSocketConstraint {
     }
 }
 
+/// 1-DOF revolute joint. The bone is kept in the plane perpendicular to `axis`
+/// (given in the parent bone's frame) and its signed angle from the parent bone,
+/// measured around `axis`, is clamped to `[min_angle, max_angle]`.
+#[derive(Debug, Clone, Copy)]
+pub struct HingeConstraint {
+    pub axis: Vec3,
+    pub min_angle: f32,
+    pub max_angle: f32,
+}
+
+impl HingeConstraint {
+    pub fn new(axis: Vec3, min_angle_degrees: f32, max_angle_degrees: f32) -> Self {
+        Self::from_radians(
+            axis,
+            min_angle_degrees.to_radians(),
+            max_angle_degrees.to_radians(),
+        )
+    }
+
+    pub fn from_radians(axis: Vec3, min_angle: f32, max_angle: f32) -> Self {
+        Self {
+            axis: axis.normalize_or_zero(),
+            min_angle: min_angle.min(max_angle),
+            max_angle: max_angle.max(min_angle),
+        }
+    }
+}
+
+impl HingeConstraint {
+    /// How far, in radians, a bone clamped straight against a limit is bent into the
+    /// allowed range. A straight bone gives the solvers no bend to swing the parent
+    /// with, so a one-sided hinge would otherwise stay locked straight whenever the
+    /// target lies on its other side.
+    const STRAIGHT_BEND: f32 = 2.0 * std::f32::consts::PI / 180.0;
+
+    /// Returns the clamped rotation about the world hinge axis together with the
+    /// zero-angle direction it applies to.
+    fn clamped_rotation(&self, direction: Vec3, parent: Quat) -> Option<(Quat, Vec3)> {
+        let axis = (parent * self.axis).normalize_or_zero();
+        let ref_dir = parent * Vec3::Y;
+
+        if axis.length_squared() < 0.0001 {
+            return None;
+        }
+
+        let zero = (ref_dir - axis * ref_dir.dot(axis)).normalize_or_zero();
+        if zero.length_squared() < 0.0001 {
+            return None;
+        }
+
+        let projected = (direction - axis * direction.dot(axis)).normalize_or_zero();
+        let angle = if projected.length_squared() < 0.0001 {
+            0.0
+        } else {
+            axis.dot(zero.cross(projected)).atan2(zero.dot(projected))
+        };
+
+        let mut clamped = angle.clamp(self.min_angle, self.max_angle);
+        if clamped != angle && clamped.abs() < Self::STRAIGHT_BEND {
+            let inward = if clamped == self.min_angle { 1.0 } else { -1.0 };
+            clamped =
+                (clamped + inward * Self::STRAIGHT_BEND).clamp(self.min_angle, self.max_angle);
+        }
+        Some((Quat::from_axis_angle(axis, clamped), zero))
+    }
+}
+
+impl Constraint for HingeConstraint {
+    fn apply(&self, direction: Vec3, reference: Vec3) -> Vec3 {
+        self.apply_in_frame(direction, frame::align(Quat::IDENTITY, reference, Vec3::Z))
+    }
+
+    fn apply_in_frame(&self, direction: Vec3, parent: Quat) -> Vec3 {
+        match self.clamped_rotation(direction, parent) {
+            Some((swing, zero)) => swing * zero,
+            None => direction.normalize_or_zero(),
+        }
+    }
+
+    /// A hinge has no roll freedom, so the result is the parent frame turned about the
+    /// hinge axis.
+    fn apply_rotation(&self, rotation: Quat, parent: Quat) -> Quat {
+        match self.clamped_rotation(rotation * Vec3::Y, parent) {
+            Some((swing, zero)) => {
+                let onto_plane = Quat::from_rotation_arc(parent * Vec3::Y, zero);
+                (swing * onto_plane * parent).normalize()
+            }
+            None => rotation,
+        }
+    }
+
+    fn constrains_twist(&self) -> bool {
+        true
+    }
+
+    fn clone_box(&self) -> Box<dyn Constraint> {
+        Box::new(*self)
+    }
+}
+
+/// Asymmetric cone from the FABRIK paper. Each half-angle limits how far the bone may
+/// swing away from the parent bone (+Y of the parent frame) towards one side:
+/// `up`/`down` towards +Z/-Z and `right`/`left` towards +X/-X.
+///
+/// The direction is projected onto the plane one unit along the parent bone, where the
+/// limits form an ellipse per quadrant; a direction outside it is moved to the closest
+/// point on that ellipse. Half-angles are kept below 90 degrees.
+#[derive(Debug, Clone, Copy)]
+pub struct EllipticalConeConstraint {
+    pub up: f32,
+    pub down: f32,
+    pub left: f32,
+    pub right: f32,
+}
+
+impl EllipticalConeConstraint {
+    const MAX_HALF_ANGLE: f32 = 89.0 * std::f32::consts::PI / 180.0;
+
+    pub fn new(up_degrees: f32, down_degrees: f32, left_degrees: f32, right_degrees: f32) -> Self {
+        Self::from_radians(
+            up_degrees.to_radians(),
+            down_degrees.to_radians(),
+            left_degrees.to_radians(),
+            right_degrees.to_radians(),
+        )
+    }
+
+    pub fn from_radians(up: f32, down: f32, left: f32, right: f32) -> Self {
+        let clamp = |angle: f32| angle.clamp(0.0, Self::MAX_HALF_ANGLE);
+        Self {
+            up: clamp(up),
+            down: clamp(down),
+            left: clamp(left),
+            right: clamp(right),
+        }
+    }
+
+    /// Closest point on the ellipse with semi-axes `a`, `b` to `(px, pz)`.
+    fn closest_on_ellipse(a: f32, b: f32, px: f32, pz: f32) -> (f32, f32) {
+        let (x0, z0) = (px.abs(), pz.abs());
+        let mut tx = std::f32::consts::FRAC_1_SQRT_2;
+        let mut tz = std::f32::consts::FRAC_1_SQRT_2;
+
+        for _ in 0..4 {
+            let x = a * tx;
+            let z = b * tz;
+            let ex = (a * a - b * b) * tx.powi(3) / a;
+            let ez = (b * b - a * a) * tz.powi(3) / b;
+
+            let (rx, rz) = (x - ex, z - ez);
+            let (qx, qz) = (x0 - ex, z0 - ez);
+            let r = rx.hypot(rz);
+            let q = qx.hypot(qz).max(f32::EPSILON);
+
+            tx = ((qx * r / q + ex) / a).clamp(0.0, 1.0);
+            tz = ((qz * r / q + ez) / b).clamp(0.0, 1.0);
+            let t = tx.hypot(tz).max(f32::EPSILON);
+            tx /= t;
+            tz /= t;
+        }
+
+        (a * tx * px.signum(), b * tz * pz.signum())
+    }
+}
+
+impl Constraint for EllipticalConeConstraint {
+    fn apply(&self, direction: Vec3, reference: Vec3) -> Vec3 {
+        self.apply_in_frame(direction, frame::align(Quat::IDENTITY, reference, Vec3::Z))
+    }
+
+    fn apply_in_frame(&self, direction: Vec3, parent: Quat) -> Vec3 {
+        let local = parent.inverse() * direction.normalize_or_zero();
+        if local.length_squared() < 0.0001 {
+            return parent * Vec3::Y;
+        }
+
+        let (x, z) = if local.y > 0.0001 {
+            (local.x / local.y, local.z / local.y)
+        } else {
+            // At or behind the cone's base plane: push far out along the same side so
+            // the projection lands on the boundary.
+            let side = Vec3::new(local.x, 0.0, local.z).normalize_or_zero();
+            if side.length_squared() < 0.0001 {
+                return parent * Vec3::Y;
+            }
+            (side.x * 1.0e4, side.z * 1.0e4)
+        };
+
+        let a = if x >= 0.0 { self.right } else { self.left };
+        let b = if z >= 0.0 { self.up } else { self.down };
+        let (a, b) = (a.tan().max(1.0e-4), b.tan().max(1.0e-4));
+
+        if (x / a).powi(2) + (z / b).powi(2) <= 1.0 {
+            return direction.normalize_or_zero();
+        }
+
+        let (cx, cz) = Self::closest_on_ellipse(a, b, x, z);
+        parent * Vec3::new(cx, 1.0, cz).normalize()
+    }
+
+    fn clone_box(&self) -> Box<dyn Constraint> {
+        Box::new(*self)
+    }
+}
+
+/// Limits a bone's rotation relative to its parent after splitting it into a swing
+/// (tilt of the bone axis away from the parent bone) and a twist (roll around the bone
+/// axis). The swing is clamped to a cone of `max_swing` and the twist to
+/// `[min_twist, max_twist]`, independently of each other.
+#[derive(Debug, Clone, Copy)]
+pub struct SwingTwistConstraint {
+    pub max_swing: f32,
+    pub min_twist: f32,
+    pub max_twist: f32,
+}
+
+impl SwingTwistConstraint {
+    pub fn new(max_swing_degrees: f32, min_twist_degrees: f32, max_twist_degrees: f32) -> Self {
+        Self::from_radians(
+            max_swing_degrees.to_radians(),
+            min_twist_degrees.to_radians(),
+            max_twist_degrees.to_radians(),
+        )
+    }
+
+    pub fn from_radians(max_swing: f32, min_twist: f32, max_twist: f32) -> Self {
+        Self {
+            max_swing: max_swing.max(0.0),
+            min_twist: min_twist.min(max_twist),
+            max_twist: max_twist.max(min_twist),
+        }
+    }
+
+    /// Splits `rotation` into `swing * twist`, with the twist around +Y.
+    pub fn decompose(rotation: Quat) -> (Quat, Quat) {
+        let twist = Quat::from_xyzw(0.0, rotation.y, 0.0, rotation.w);
+        let twist = if twist.length_squared() < 1e-8 {
+            Quat::IDENTITY
+        } else {
+            twist.normalize()
+        };
+        ((rotation * twist.inverse()).normalize(), twist)
+    }
+
+    fn clamp_swing(&self, swing: Quat) -> Quat {
+        let (axis, angle) = swing.to_axis_angle();
+        if angle <= self.max_swing {
+            swing
+        } else {
+            Quat::from_axis_angle(axis, self.max_swing)
+        }
+    }
+}
+
+impl Constraint for SwingTwistConstraint {
+    fn apply(&self, direction: Vec3, reference: Vec3) -> Vec3 {
+        BallSocketConstraint::from_radians(self.max_swing).apply(direction, reference)
+    }
+
+    fn apply_rotation(&self, rotation: Quat, parent: Quat) -> Quat {
+        let relative = (parent.inverse() * rotation).normalize();
+        let (swing, twist) = Self::decompose(relative);
+
+        let twist_angle = 2.0 * twist.y.atan2(twist.w);
+        let twist_angle = if twist_angle > std::f32::consts::PI {
+            twist_angle - std::f32::consts::TAU
+        } else if twist_angle < -std::f32::consts::PI {
+            twist_angle + std::f32::consts::TAU
+        } else {
+            twist_angle
+        };
+        let twist = Quat::from_rotation_y(twist_angle.clamp(self.min_twist, self.max_twist));
+
+        (parent * self.clamp_swing(swing) * twist).normalize()
+    }
+
+    fn constrains_twist(&self) -> bool {
+        true
+    }
+
+    fn clone_box(&self) -> Box<dyn Constraint> {
+        Box::new(*self)
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct NoConstraint;
 
@@ -0,0 +1,49 @@
+use std::fmt;
+
+/// Why a chain couldn't be built or solved.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChainError {
+    /// The builder has no joints.
+    NoJoints,
+    /// The builder has a single joint, so there is no bone to solve.
+    SingleJoint,
+    /// Joints `bone` and `bone + 1` sit on the same point, giving a zero-length bone.
+    CoincidentJoints { bone: usize },
+    /// Joint `joint` has a NaN or infinite coordinate.
+    NonFinitePosition { joint: usize },
+    /// The position tolerance must be finite and greater than zero.
+    InvalidTolerance(f32),
+    /// A length range was given for a bone the chain does not have.
+    LengthRangeOutOfBounds { bone: usize, bone_count: usize },
+    /// The solve target has a NaN or infinite component.
+    NonFiniteTarget,
+}
+
+impl fmt::Display for ChainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoJoints => write!(f, "chain has no joints"),
+            Self::SingleJoint => write!(f, "chain needs at least two joints, got one"),
+            Self::CoincidentJoints { bone } => {
+                write!(
+                    f,
+                    "joints {bone} and {} coincide, giving a zero-length bone",
+                    bone + 1
+                )
+            }
+            Self::NonFinitePosition { joint } => {
+                write!(f, "joint {joint} has a non-finite position")
+            }
+            Self::InvalidTolerance(tolerance) => {
+                write!(f, "tolerance must be positive and finite, got {tolerance}")
+            }
+            Self::LengthRangeOutOfBounds { bone, bone_count } => write!(
+                f,
+                "length range for bone {bone} but the chain has {bone_count} bones"
+            ),
+            Self::NonFiniteTarget => write!(f, "target is not finite"),
+        }
+    }
+}
+
+impl std::error::Error for ChainError {}
@@ -0,0 +1,43 @@
+//! Helpers for building bone frames from joint positions.
+//!
+//! A bone frame has its local +Y axis along the bone, matching
+//! [`Mesh::create_bone_transform`](crate::render::Mesh::create_bone_transform).
+
+use glam::{Mat3, Quat, Vec3};
+
+/// Builds a rotation whose +Y axis is `direction` and whose +Z axis points as close
+/// to `up` as possible.
+pub(crate) fn from_direction(direction: Vec3, up: Vec3) -> Quat {
+    let y = direction.normalize_or_zero();
+    if y.length_squared() < 0.0001 {
+        return Quat::IDENTITY;
+    }
+
+    let mut z = up - y * up.dot(y);
+    if z.length_squared() < 1e-6 {
+        z = y.any_orthonormal_vector();
+    }
+    let z = z.normalize();
+    let x = y.cross(z);
+
+    Quat::from_mat3(&Mat3::from_cols(x, y, z)).normalize()
+}
+
+/// Rotates `rotation` by the shortest arc that brings its +Y axis onto `direction`,
+/// which keeps the twist around the bone as close as possible to the previous one.
+///
+/// A near half-turn has no well defined shortest arc, so the frame is rebuilt from
+/// `up` instead of letting the roll flip arbitrarily.
+pub(crate) fn align(rotation: Quat, direction: Vec3, up: Vec3) -> Quat {
+    let current = rotation * Vec3::Y;
+    let direction = direction.normalize_or_zero();
+    if direction.length_squared() < 0.0001 {
+        return rotation;
+    }
+
+    if current.dot(direction) < -0.999 {
+        return from_direction(direction, up);
+    }
+
+    (Quat::from_rotation_arc(current, direction) * rotation).normalize()
+}
@@ -0,0 +1,130 @@
+use super::chain::Chain;
+use super::solver::{FabrikSolver, Goal, IkSolver, SolveResult, SolveTrace};
+use crate::collision::{CollisionConfig, ObstacleWorld};
+use glam::{Mat3, Quat, Vec3};
+
+/// Damped least squares Jacobian solver.
+///
+/// Every joint gets three rotational degrees of freedom about the world axes. Each
+/// iteration takes the step `Δθ = Jᵀ (J Jᵀ + λ² I)⁻¹ e` towards the target, where `λ` is
+/// the damping: larger values are slower but stay smooth near singular (fully stretched)
+/// poses. Constraints, the pole target and collisions are applied after every step.
+#[derive(Debug, Clone, Copy)]
+pub struct JacobianSolver {
+    damping: f32,
+    max_step: f32,
+}
+
+impl JacobianSolver {
+    pub fn new(damping: f32) -> Self {
+        Self {
+            damping,
+            ..Default::default()
+        }
+    }
+
+    /// Longest end effector move attempted per iteration, as a fraction of the chain's
+    /// total length. Defaults to 0.2.
+    pub fn max_step(mut self, max_step: f32) -> Self {
+        self.max_step = max_step;
+        self
+    }
+
+    pub fn damping(&self) -> f32 {
+        self.damping
+    }
+
+    fn solve_inner(
+        &self,
+        chain: &mut Chain,
+        target: Vec3,
+        collision: Option<(&ObstacleWorld, &CollisionConfig)>,
+    ) -> SolveResult {
+        let n = chain.joints.len();
+        if !target.is_finite() {
+            return SolveResult::rejected();
+        }
+        if n < 2 {
+            return SolveResult::trivial();
+        }
+
+        let base = chain.joints[0].position;
+        chain.fit_stretch(base, target);
+        let base_frame = chain.base_frame();
+        let goal = Goal::position(target);
+        let tolerance = chain.tolerance();
+        let max_step = self.max_step * chain.total_length();
+        let mut trace = SolveTrace::new(chain);
+
+        for _ in 0..chain.max_iterations {
+            self.step(chain, target, max_step);
+            FabrikSolver::settle(chain, goal, base, base_frame, collision, &mut trace);
+
+            let distance = (chain.joints[n - 1].position - target).length();
+            trace.record(distance);
+            if distance <= tolerance || trace.is_stalled() {
+                break;
+            }
+        }
+
+        chain.update_frames();
+        trace.finish(chain, goal, base)
+    }
+
+    fn step(&self, chain: &mut Chain, target: Vec3, max_step: f32) {
+        let n = chain.joints.len();
+        let end = chain.joints[n - 1].position;
+        let error = (target - end).clamp_length_max(max_step);
+
+        // Column block of joint i for the three world axes is [x × r, y × r, z × r] with
+        // r the lever arm to the end effector, so J Jᵀ sums to |r|² I - r rᵀ per joint.
+        let levers: Vec<Vec3> = chain.joints[..n - 1]
+            .iter()
+            .map(|j| end - j.position)
+            .collect();
+        let mut jjt = Mat3::from_diagonal(Vec3::splat(self.damping * self.damping));
+        for r in &levers {
+            jjt += Mat3::from_diagonal(Vec3::splat(r.length_squared()))
+                - Mat3::from_cols(*r * r.x, *r * r.y, *r * r.z);
+        }
+        if jjt.determinant().abs() < f32::EPSILON {
+            return;
+        }
+        let y = jjt.inverse() * error;
+
+        // Jᵢᵀ y = r × y is joint i's rotation vector. Applying from the tip inwards keeps
+        // every rotation pivoting on a joint that has not moved yet.
+        for i in (0..n - 1).rev() {
+            let rotation = Quat::from_scaled_axis(levers[i].cross(y));
+            let pivot = chain.joints[i].position;
+            for joint in &mut chain.joints[i + 1..] {
+                joint.position = pivot + rotation * (joint.position - pivot);
+            }
+        }
+    }
+}
+
+impl Default for JacobianSolver {
+    fn default() -> Self {
+        Self {
+            damping: 0.5,
+            max_step: 0.2,
+        }
+    }
+}
+
+impl IkSolver for JacobianSolver {
+    fn solve(&self, chain: &mut Chain, target: Vec3) -> SolveResult {
+        self.solve_inner(chain, target, None)
+    }
+
+    fn solve_with_collision(
+        &self,
+        chain: &mut Chain,
+        target: Vec3,
+        world: &ObstacleWorld,
+        config: &CollisionConfig,
+    ) -> SolveResult {
+        self.solve_inner(chain, target, Some((world, config)))
+    }
+}
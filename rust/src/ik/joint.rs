@@ -1,10 +1,54 @@
 use super::constraint::Constraint;
-use glam::Vec3;
+use crate::math::Transform;
+use glam::{Quat, Vec3};
+
+/// A point an intermediate joint is pulled towards while solving.
+///
+/// `weight` runs from 0 (ignored) to 1. Partial weights bend the bones around the joint
+/// towards the point without changing their length, and settle on a compromise when the
+/// point and the end effector target cannot both be met. A weight of 1 pins the joint
+/// there, and the solver treats it as a sub-base that the chain on either side has to
+/// reach.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JointTarget {
+    pub position: Vec3,
+    pub weight: f32,
+}
+
+impl JointTarget {
+    pub fn new(position: Vec3, weight: f32) -> Self {
+        Self {
+            position,
+            weight: weight.clamp(0.0, 1.0),
+        }
+    }
+
+    pub fn pinned(position: Vec3) -> Self {
+        Self::new(position, 1.0)
+    }
+
+    pub fn is_pinned(&self) -> bool {
+        self.weight >= 1.0
+    }
+
+    /// Where a joint currently at `position` should be aimed at.
+    pub(crate) fn aim(&self, position: Vec3) -> Vec3 {
+        position.lerp(self.position, self.weight.clamp(0.0, 1.0))
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct Joint {
     pub position: Vec3,
     pub constraint: Option<Box<dyn Constraint>>,
+    /// Optional mid-chain target. Ignored on the base and end effector, which are driven
+    /// by the solve itself.
+    pub target: Option<JointTarget>,
+    /// Frame relative to the parent joint's world frame (the chain's space for the base).
+    pub local_frame: Transform,
+    /// Frame in world space. Its +Y axis points along the outgoing bone; the end
+    /// effector shares the rotation of the last bone.
+    pub world_frame: Transform,
 }
 
 impl Joint {
@@ -12,9 +56,20 @@ impl Joint {
         Self {
             position,
             constraint: None,
+            target: None,
+            local_frame: Transform::from_position(position),
+            world_frame: Transform::from_position(position),
         }
     }
 
+    pub fn world_rotation(&self) -> Quat {
+        self.world_frame.rotation
+    }
+
+    pub fn local_rotation(&self) -> Quat {
+        self.local_frame.rotation
+    }
+
     pub fn with_constraint<C: Constraint + 'static>(mut self, constraint: C) -> Self {
         self.constraint = Some(Box::new(constraint));
         self
@@ -28,10 +83,50 @@ impl Joint {
         self.constraint = None;
     }
 
+    pub fn with_target(mut self, target: JointTarget) -> Self {
+        self.target = Some(target);
+        self
+    }
+
+    pub fn is_pinned(&self) -> bool {
+        self.target.is_some_and(|t| t.is_pinned())
+    }
+
+    /// Where the solver should aim this joint instead of its current position.
+    pub(crate) fn aim(&self, position: Vec3) -> Vec3 {
+        match &self.target {
+            Some(target) => target.aim(position),
+            None => position,
+        }
+    }
+
     pub fn apply_constraint(&self, direction: Vec3, reference: Vec3) -> Vec3 {
         match &self.constraint {
             Some(c) => c.apply(direction, reference),
             None => direction.normalize_or_zero(),
         }
     }
+
+    pub fn apply_constraint_in_frame(&self, direction: Vec3, parent: Quat) -> Vec3 {
+        match &self.constraint {
+            Some(c) => c.apply_in_frame(direction, parent),
+            None => direction.normalize_or_zero(),
+        }
+    }
+
+    pub fn apply_constraint_rotation(&self, rotation: Quat, parent: Quat) -> Quat {
+        match &self.constraint {
+            Some(c) => c.apply_rotation(rotation, parent),
+            None => rotation,
+        }
+    }
+
+    /// Rotation a bone frame is aligned from: the joint's own previous rotation when
+    /// its constraint tracks twist, otherwise the parent's so roll does not drift.
+    pub(crate) fn frame_seed(&self, parent: Quat) -> Quat {
+        match &self.constraint {
+            Some(c) if c.constrains_twist() => self.world_frame.rotation,
+            _ => parent,
+        }
+    }
 }
\ No newline at end of file
@@ -1,13 +1,30 @@
 //! Inverse Kinematics module
 //!
-//! This module contains the core IK types and FABRIK solver implementation.
+//! This module contains the core IK types and the FABRIK, CCD, Jacobian and analytic
+//! two-bone solvers.
 
+pub mod blend;
+pub mod ccd;
 pub mod chain;
 pub mod constraint;
+pub mod error;
+pub(crate) mod frame;
+pub mod jacobian;
 pub mod joint;
+pub mod skeleton;
 pub mod solver;
+pub mod two_bone;
 
-pub use chain::{Chain, ChainBuilder};
-pub use constraint::{BallSocketConstraint, Constraint};
-pub use joint::Joint;
-pub use solver::{FabrikSolver, SolveResult};
\ No newline at end of file
+pub use blend::IkFkBlend;
+pub use ccd::CcdSolver;
+pub use chain::{Chain, ChainBuilder, LengthRange, RootMode};
+pub use constraint::{
+    BallSocketConstraint, Constraint, EllipticalConeConstraint, HingeConstraint,
+    SwingTwistConstraint,
+};
+pub use error::ChainError;
+pub use jacobian::JacobianSolver;
+pub use joint::{Joint, JointTarget};
+pub use skeleton::{Branch, Skeleton, SkeletonBuilder};
+pub use solver::{FabrikSolver, IkSolver, SolveResult, Termination};
+pub use two_bone::TwoBoneSolver;
\ No newline at end of file
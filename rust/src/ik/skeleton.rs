@@ -0,0 +1,217 @@
+use super::chain::Chain;
+use glam::Vec3;
+
+/// A chain segment of a [`Skeleton`] together with the branch it hangs off.
+///
+/// The first joint of a child branch is the sub-base: it always coincides with
+/// the end joint of its parent branch.
+#[derive(Debug, Clone)]
+pub struct Branch {
+    pub(crate) chain: Chain,
+    pub(crate) parent: Option<usize>,
+}
+
+impl Branch {
+    pub fn chain(&self) -> &Chain {
+        &self.chain
+    }
+
+    pub fn parent(&self) -> Option<usize> {
+        self.parent
+    }
+}
+
+/// A tree of [`Chain`] segments, e.g. a torso with two arms or a hand with five fingers.
+///
+/// Branches are stored so that a parent always comes before its children, with the
+/// root branch at index 0.
+#[derive(Debug, Clone)]
+pub struct Skeleton {
+    pub(crate) branches: Vec<Branch>,
+    pub(crate) tolerance: f32,
+    pub(crate) stall_threshold: f32,
+    pub(crate) record_error_history: bool,
+    pub(crate) max_iterations: u32,
+}
+
+impl Skeleton {
+    pub fn builder() -> SkeletonBuilder {
+        SkeletonBuilder::new()
+    }
+
+    pub fn branches(&self) -> &[Branch] {
+        &self.branches
+    }
+
+    pub fn branch_count(&self) -> usize {
+        self.branches.len()
+    }
+
+    pub fn chain(&self, branch: usize) -> Option<&Chain> {
+        self.branches.get(branch).map(|b| &b.chain)
+    }
+
+    pub fn parent(&self, branch: usize) -> Option<usize> {
+        self.branches.get(branch).and_then(|b| b.parent)
+    }
+
+    pub fn children(&self, branch: usize) -> impl Iterator<Item = usize> + '_ {
+        self.branches
+            .iter()
+            .enumerate()
+            .filter(move |(_, b)| b.parent == Some(branch))
+            .map(|(i, _)| i)
+    }
+
+    pub fn is_leaf(&self, branch: usize) -> bool {
+        self.children(branch).next().is_none()
+    }
+
+    /// Indices of the branches whose last joint is an end effector.
+    pub fn end_effectors(&self) -> Vec<usize> {
+        (0..self.branches.len())
+            .filter(|&i| self.is_leaf(i))
+            .collect()
+    }
+
+    pub fn root(&self) -> Option<Vec3> {
+        self.branches.first().and_then(|b| b.chain.base())
+    }
+
+    pub fn tolerance(&self) -> f32 {
+        self.tolerance
+    }
+
+    /// Like [`Chain::stall_threshold`], as a fraction of the skeleton's tolerance. The
+    /// branches' own settings don't apply to skeleton solves.
+    pub fn stall_threshold(&self) -> f32 {
+        self.stall_threshold
+    }
+
+    pub fn set_stall_threshold(&mut self, threshold: f32) {
+        self.stall_threshold = threshold.max(0.0);
+    }
+
+    /// Whether skeleton solves fill in
+    /// [`SolveResult::error_history`](super::SolveResult::error_history).
+    pub fn record_error_history(&self) -> bool {
+        self.record_error_history
+    }
+
+    pub fn set_record_error_history(&mut self, record: bool) {
+        self.record_error_history = record;
+    }
+
+    pub fn max_iterations(&self) -> u32 {
+        self.max_iterations
+    }
+}
+
+pub struct SkeletonBuilder {
+    branches: Vec<Branch>,
+    tolerance: f32,
+    stall_threshold: f32,
+    record_error_history: bool,
+    max_iterations: u32,
+}
+
+impl SkeletonBuilder {
+    pub fn new() -> Self {
+        Self {
+            branches: Vec::new(),
+            tolerance: 0.001,
+            stall_threshold: 0.0,
+            record_error_history: false,
+            max_iterations: 10,
+        }
+    }
+
+    /// Sets the root branch. Must be called before any [`Self::add_child`].
+    pub fn root(mut self, chain: Chain) -> Self {
+        let root = Branch {
+            chain,
+            parent: None,
+        };
+        match self.branches.first_mut() {
+            Some(first) => *first = root,
+            None => self.branches.push(root),
+        }
+        self
+    }
+
+    /// Attaches `chain` to the end joint of branch `parent`.
+    ///
+    /// The chain is translated so that its first joint sits on the parent's end joint.
+    ///
+    /// # Panics
+    /// Panics if `parent` does not refer to a branch added earlier.
+    pub fn add_child(mut self, parent: usize, chain: Chain) -> Self {
+        assert!(
+            parent < self.branches.len(),
+            "parent branch {parent} must be added before its children"
+        );
+        self.branches.push(Branch {
+            chain,
+            parent: Some(parent),
+        });
+        self
+    }
+
+    pub fn tolerance(mut self, tolerance: f32) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// See [`Skeleton::stall_threshold`]. Defaults to 0 (off).
+    pub fn stall_threshold(mut self, threshold: f32) -> Self {
+        self.stall_threshold = threshold.max(0.0);
+        self
+    }
+
+    /// See [`Skeleton::set_record_error_history`].
+    pub fn record_error_history(mut self, record: bool) -> Self {
+        self.record_error_history = record;
+        self
+    }
+
+    pub fn max_iterations(mut self, max_iterations: u32) -> Self {
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    pub fn build(mut self) -> Skeleton {
+        for i in 0..self.branches.len() {
+            let Some(parent) = self.branches[i].parent else {
+                continue;
+            };
+            let (Some(sub_base), Some(base)) = (
+                self.branches[parent].chain.end_effector(),
+                self.branches[i].chain.base(),
+            ) else {
+                continue;
+            };
+
+            let offset = sub_base - base;
+            let chain = &mut self.branches[i].chain;
+            for joint in chain.joints.iter_mut() {
+                joint.position += offset;
+            }
+            // Moves the root anchor, frames and rest pose along with the joints.
+            chain.after_structure_edit(0);
+        }
+
+        Skeleton {
+            branches: self.branches,
+            tolerance: self.tolerance,
+            stall_threshold: self.stall_threshold,
+            record_error_history: self.record_error_history,
+            max_iterations: self.max_iterations,
+        }
+    }
+}
+
+impl Default for SkeletonBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,127 +1,837 @@
-use super::chain::Chain;
+use super::ccd::CcdSolver;
+use super::chain::{Chain, RootMode};
+use super::error::ChainError;
+use super::frame;
+use super::joint::Joint;
+use super::skeleton::Skeleton;
 use crate::collision::{CollisionConfig, CollisionResponse, ObstacleWorld};
-use glam::Vec3;
+use crate::math::Transform;
+use glam::{Quat, Vec3};
+use std::ops::AddAssign;
+
+/// Rotations closer than this (as `1 - |q1 · q2|`) are not counted as a clamp.
+const CLAMP_EPSILON: f32 = 1e-6;
+
+/// Consecutive stalled iterations after which a solve gives up.
+const STALL_PATIENCE: u32 = 2;
+
+/// Share of the total length a straight chain's middle joints are pushed towards the
+/// pole so FABRIK has a bend to work with.
+const POLE_SEED_BEND: f32 = 0.1;
+
+/// Why a solve stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Termination {
+    /// The end effector is within tolerance of the target.
+    Converged,
+    /// The target is further from the base than the chain can reach, or isn't finite.
+    Unreachable,
+    /// The iteration budget ran out while the error was still improving.
+    MaxIterations,
+    /// The error stopped improving before reaching the target, typically because joint
+    /// constraints or pinned joints hold the chain back.
+    Stalled,
+    /// The chain ended up against an obstacle short of the target.
+    CollisionBlocked,
+}
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct SolveResult {
     pub converged: bool,
+    pub termination: Termination,
     pub iterations: u32,
     pub final_distance: f32,
+    /// Distance to the target after each iteration, when the chain records it; see
+    /// [`Chain::set_record_error_history`].
+    pub error_history: Option<Vec<f32>>,
+    /// Number of times a joint constraint changed a bone direction during the solve.
+    pub constraint_clamps: u32,
+    /// Largest angle in radians a joint constraint had to turn a bone by during the
+    /// solve. Large values mean the chain was pushing hard against its limits.
+    pub max_constraint_violation: f32,
+    /// Angle in radians between the last bone's rotation and the pose target's rotation.
+    /// Always zero for position-only solves.
+    pub orientation_error: f32,
+    /// How far the base ended up from its anchor. Always zero for pinned roots.
+    pub root_displacement: Vec3,
+    /// Stretch factor of every bone; all 1 unless the chain has length ranges.
+    pub bone_scales: Vec<f32>,
+}
+
+impl SolveResult {
+    pub(crate) fn trivial() -> Self {
+        Self {
+            converged: true,
+            termination: Termination::Converged,
+            iterations: 0,
+            final_distance: 0.0,
+            error_history: None,
+            constraint_clamps: 0,
+            max_constraint_violation: 0.0,
+            orientation_error: 0.0,
+            root_displacement: Vec3::ZERO,
+            bone_scales: Vec::new(),
+        }
+    }
+
+    /// Result of a solve refused before touching the chain, e.g. for a non-finite target.
+    pub(crate) fn rejected() -> Self {
+        Self {
+            converged: false,
+            termination: Termination::Unreachable,
+            final_distance: f32::INFINITY,
+            ..Self::trivial()
+        }
+    }
+}
+
+/// Constraint clamps counted over one or more passes.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Clamps {
+    count: u32,
+    max_angle: f32,
+}
+
+impl Clamps {
+    /// Counts a clamp if `constrained` differs from `rotation`, and returns whether it did.
+    fn record(&mut self, rotation: Quat, constrained: Quat) -> bool {
+        if rotation.dot(constrained).abs() >= 1.0 - CLAMP_EPSILON {
+            return false;
+        }
+        self.count += 1;
+        self.max_angle = self.max_angle.max(rotation.angle_between(constrained));
+        true
+    }
+}
+
+impl AddAssign for Clamps {
+    fn add_assign(&mut self, other: Self) {
+        self.count += other.count;
+        self.max_angle = self.max_angle.max(other.max_angle);
+    }
+}
+
+/// Bookkeeping shared by the solvers' iteration loops, turned into a [`SolveResult`]
+/// once they stop.
+pub(crate) struct SolveTrace {
+    pub(crate) clamps: Clamps,
+    /// Whether collision response moved any joint in the latest iteration.
+    collided: bool,
+    iterations: u32,
+    last_error: f32,
+    stall_threshold: f32,
+    stalled_iterations: u32,
+    history: Option<Vec<f32>>,
+}
+
+impl SolveTrace {
+    pub(crate) fn new(chain: &Chain) -> Self {
+        Self::with_settings(
+            chain.tolerance(),
+            chain.stall_threshold,
+            chain.record_error_history,
+        )
+    }
+
+    /// Trace for a solve converging to `tolerance`, with the stall threshold given as a
+    /// fraction of it.
+    pub(crate) fn with_settings(
+        tolerance: f32,
+        stall_threshold: f32,
+        record_history: bool,
+    ) -> Self {
+        Self {
+            clamps: Clamps::default(),
+            collided: false,
+            iterations: 0,
+            last_error: f32::INFINITY,
+            stall_threshold: stall_threshold * tolerance,
+            stalled_iterations: 0,
+            history: record_history.then(Vec::new),
+        }
+    }
+
+    /// Records the error left after an iteration.
+    pub(crate) fn record(&mut self, error: f32) {
+        self.iterations += 1;
+        if self.last_error - error <= self.stall_threshold {
+            self.stalled_iterations += 1;
+        } else {
+            self.stalled_iterations = 0;
+        }
+        self.last_error = error;
+        if let Some(history) = &mut self.history {
+            history.push(error);
+        }
+    }
+
+    /// Whether the error has stopped improving for long enough to give up. Never true
+    /// when the chain's stall threshold is 0.
+    pub(crate) fn is_stalled(&self) -> bool {
+        self.stall_threshold > 0.0 && self.stalled_iterations >= STALL_PATIENCE
+    }
+
+    /// Termination of a loop that ran out of iterations or stopped early without
+    /// reaching the target. Only [`Termination::Stalled`] if the stall check fired.
+    pub(crate) fn unconverged(&self) -> Termination {
+        if self.is_stalled() {
+            Termination::Stalled
+        } else {
+            Termination::MaxIterations
+        }
+    }
+
+    /// Measures how close the chain got to `goal` and why the solve stopped.
+    pub(crate) fn finish(self, chain: &Chain, goal: Goal, base: Vec3) -> SolveResult {
+        let final_distance = FabrikSolver::goal_distance(chain, goal);
+        let orientation_error = FabrikSolver::orientation_error(chain, goal);
+        let tolerance = chain.tolerance();
+        let termination =
+            if final_distance <= tolerance && orientation_error <= chain.orientation_tolerance {
+                Termination::Converged
+            } else if (goal.position - base).length() > chain.total_length() + tolerance {
+                Termination::Unreachable
+            } else if self.collided {
+                Termination::CollisionBlocked
+            } else {
+                self.unconverged()
+            };
+        self.into_result(
+            final_distance,
+            orientation_error,
+            termination,
+            chain.bone_scales.clone(),
+        )
+    }
+
+    pub(crate) fn into_result(
+        self,
+        final_distance: f32,
+        orientation_error: f32,
+        termination: Termination,
+        bone_scales: Vec<f32>,
+    ) -> SolveResult {
+        SolveResult {
+            converged: termination == Termination::Converged,
+            termination,
+            iterations: self.iterations,
+            final_distance,
+            error_history: self.history,
+            constraint_clamps: self.clamps.count,
+            max_constraint_violation: self.clamps.max_angle,
+            orientation_error,
+            root_displacement: Vec3::ZERO,
+            bone_scales,
+        }
+    }
+}
+
+/// What a single-chain solve is reaching for: the end effector position, and for pose
+/// targets the rotation of the last bone.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Goal {
+    position: Vec3,
+    rotation: Option<Quat>,
+}
+
+impl Goal {
+    pub(crate) fn position(position: Vec3) -> Self {
+        Self {
+            position,
+            rotation: None,
+        }
+    }
+
+    fn pose(target: Transform) -> Self {
+        Self {
+            position: target.position,
+            rotation: Some(target.rotation.normalize()),
+        }
+    }
+
+    fn is_finite(&self) -> bool {
+        self.position.is_finite() && self.rotation.is_none_or(|r| r.is_finite())
+    }
+}
+
+/// Common interface of the IK algorithms, so they can be swapped and compared on the
+/// same chains and obstacles.
+///
+/// Every solver enforces bone lengths, joint constraints and the chain's pole target, and
+/// leaves the chain's frames up to date. Only [`FabrikSolver`] honours the chain's
+/// [`RootMode`]; the others keep the base joint where it is. A non-finite target leaves
+/// the chain untouched and gives a result that hasn't converged.
+pub trait IkSolver {
+    fn solve(&self, chain: &mut Chain, target: Vec3) -> SolveResult;
+
+    /// [`Self::solve`], reporting a non-finite target as an error.
+    fn try_solve(&self, chain: &mut Chain, target: Vec3) -> Result<SolveResult, ChainError> {
+        check_target(target)?;
+        Ok(self.solve(chain, target))
+    }
+
+    fn solve_with_collision(
+        &self,
+        chain: &mut Chain,
+        target: Vec3,
+        world: &ObstacleWorld,
+        config: &CollisionConfig,
+    ) -> SolveResult;
 }
 
 pub struct FabrikSolver;
 
 impl FabrikSolver {
+    /// Solves from the chain's own base, which stays put unless the chain has a
+    /// [`RootMode`] other than [`RootMode::Pinned`].
     pub fn solve(chain: &mut Chain, target: Vec3) -> SolveResult {
-        if chain.joints.is_empty() {
-            return SolveResult {
-                converged: true,
-                iterations: 0,
-                final_distance: 0.0,
-            };
-        }
+        let result = Self::solve_rooted(chain, Goal::position(target), None);
+        chain.update_frames();
+        result
+    }
 
-        let base = chain.joints[0].position;
-        Self::solve_anchored(chain, target, base)
+    /// [`Self::solve`], reporting a non-finite target as an error.
+    pub fn try_solve(chain: &mut Chain, target: Vec3) -> Result<SolveResult, ChainError> {
+        check_target(target)?;
+        Ok(Self::solve(chain, target))
     }
 
+    /// Solves with the base pinned at `base`, whatever the chain's root mode.
     pub fn solve_anchored(chain: &mut Chain, target: Vec3, base: Vec3) -> SolveResult {
+        let result = Self::solve_goal(chain, Goal::position(target), base, None);
+        chain.update_frames();
+        result
+    }
+
+    /// Solves for a full end effector pose: the end joint reaches `target.position` and
+    /// the last bone takes `target.rotation`, so its +Y axis gives the approach direction
+    /// and the rest of the rotation its roll.
+    pub fn solve_pose(chain: &mut Chain, target: Transform) -> SolveResult {
+        let result = Self::solve_rooted(chain, Goal::pose(target), None);
+        chain.refresh_frames(true);
+        result
+    }
+
+    /// [`Self::solve_pose`], reporting a non-finite target as an error.
+    pub fn try_solve_pose(chain: &mut Chain, target: Transform) -> Result<SolveResult, ChainError> {
+        if !Goal::pose(target).is_finite() {
+            return Err(ChainError::NonFiniteTarget);
+        }
+        Ok(Self::solve_pose(chain, target))
+    }
+
+    pub fn solve_pose_anchored(chain: &mut Chain, target: Transform, base: Vec3) -> SolveResult {
+        let result = Self::solve_goal(chain, Goal::pose(target), base, None);
+        chain.refresh_frames(true);
+        result
+    }
+
+    fn solve_rooted(
+        chain: &mut Chain,
+        goal: Goal,
+        collision: Option<(&ObstacleWorld, &CollisionConfig)>,
+    ) -> SolveResult {
+        if !goal.is_finite() {
+            return SolveResult::rejected();
+        }
+        let Some(base) = chain.base() else {
+            return SolveResult::trivial();
+        };
+        if chain.root_mode == RootMode::Pinned {
+            return Self::solve_goal(chain, goal, base, collision);
+        }
+
+        // A moving root brings the target just within reach, where solve_goal lays the
+        // chain straight at it; iterating on a nearly stretched chain converges slowly
+        // and never bends one lying along the target's line.
+        let anchor = chain.root_anchor;
+        let reach = chain.total_length();
+        let offset = chain
+            .root_mode
+            .offset(anchor, goal.position, reach, chain.root_weight);
+        let mut result = Self::solve_goal(chain, goal, anchor + offset, collision);
+        result.root_displacement = offset;
+        result
+    }
+
+    fn solve_goal(
+        chain: &mut Chain,
+        goal: Goal,
+        base: Vec3,
+        collision: Option<(&ObstacleWorld, &CollisionConfig)>,
+    ) -> SolveResult {
         let joint_count = chain.joints.len();
 
+        if !goal.is_finite() || !base.is_finite() {
+            return SolveResult::rejected();
+        }
         if joint_count < 2 {
-            return SolveResult {
-                converged: true,
-                iterations: 0,
-                final_distance: 0.0,
-            };
+            return SolveResult::trivial();
         }
 
+        chain.fit_stretch(base, goal.position);
         let total_length = chain.total_length();
-        let distance_to_target = (target - base).length();
-
-        if distance_to_target > total_length {
-            Self::stretch_towards_target(chain, base, target);
-            return SolveResult {
-                converged: false,
-                iterations: 1,
-                final_distance: distance_to_target - total_length,
-            };
+        let distance_to_target = (goal.position - base).length();
+        let base_frame = chain.base_frame();
+        let tolerance = chain.tolerance();
+        let mut trace = SolveTrace::new(chain);
+
+        let pinned = chain.joints.iter().any(Joint::is_pinned);
+
+        if distance_to_target + tolerance >= total_length && !pinned {
+            // A single pass lays the chain out straight towards the target; iterating
+            // further can't get any closer. Targets within tolerance of full reach are
+            // included, so rounding in a moved base can't leave one just short of it.
+            Self::stretch_towards_target(chain, base, goal.position);
+            trace.clamps += Self::backward_pass(chain, base, base_frame, goal.rotation);
+            if let Some((world, config)) = collision {
+                trace.collided = CollisionResponse::resolve_chain(chain, world, config);
+                trace.clamps += Self::backward_pass(chain, base, base_frame, goal.rotation);
+            }
+            trace.record(Self::goal_distance(chain, goal));
+            return trace.finish(chain, goal, base);
         }
 
-        let tolerance = chain.tolerance;
-        let max_iterations = chain.max_iterations;
+        if let Some(pole) = chain.pole_target {
+            Self::seed_pole_bend(chain, pole, goal, base);
+        }
 
-        for iteration in 0..max_iterations {
-            Self::forward_pass(chain, target);
-            Self::backward_pass(chain, base);
+        let orientation_tolerance = chain.orientation_tolerance;
+        // Sub-chains may only be turned about joints past the last one with a target,
+        // which would otherwise be dragged off it.
+        let sweep_from = (goal.rotation.is_none()
+            && chain.joints.iter().any(|j| j.constraint.is_some()))
+        .then(|| {
+            chain
+                .joints
+                .iter()
+                .rposition(|j| j.target.is_some())
+                .unwrap_or(0)
+        });
+
+        for iteration in 0..chain.max_iterations {
+            // The rest bias halves every iteration so it shapes the pose without
+            // keeping the end effector from converging.
+            let rest_strength = chain.rest_bias * 0.5f32.powi(iteration as i32);
+            if rest_strength > 0.0 {
+                chain.blend_towards_rest(base, rest_strength);
+            }
+            trace.clamps += Self::forward_pass(chain, goal, base_frame);
+            if let Some(first) = sweep_from {
+                // Clamped passes settle with the chain turned away from the target, so
+                // re-anchor it and turn each sub-chain towards the target within its
+                // joint's limits before the usual settle.
+                trace.clamps += Self::backward_pass(chain, base, base_frame, None);
+                CcdSolver::sweep(chain, goal.position, base_frame, first, &mut trace.clamps);
+            }
+            Self::settle(chain, goal, base, base_frame, collision, &mut trace);
+
+            let distance = Self::goal_distance(chain, goal);
+            trace.record(distance);
+            let converged = distance <= tolerance
+                && Self::orientation_error(chain, goal) <= orientation_tolerance;
+            if converged || trace.is_stalled() {
+                break;
+            }
+        }
 
-            let end_effector = chain.joints.last().unwrap().position;
-            let distance = (end_effector - target).length();
+        trace.finish(chain, goal, base)
+    }
 
-            if distance <= tolerance {
-                return SolveResult {
-                    converged: true,
-                    iterations: iteration + 1,
-                    final_distance: distance,
-                };
+    /// Shared tail of an iteration for every solver: re-anchors the chain at `base`,
+    /// enforcing bone lengths and constraints, then applies the pole hint and obstacle
+    /// collision, re-anchoring after each. Clamps and collisions go into `trace`.
+    pub(crate) fn settle(
+        chain: &mut Chain,
+        goal: Goal,
+        base: Vec3,
+        base_frame: Quat,
+        collision: Option<(&ObstacleWorld, &CollisionConfig)>,
+        trace: &mut SolveTrace,
+    ) {
+        trace.clamps += Self::backward_pass(chain, base, base_frame, goal.rotation);
+
+        if let Some(pole) = chain.pole_target {
+            if Self::bend_towards_pole(chain, pole, goal) {
+                trace.clamps += Self::backward_pass(chain, base, base_frame, goal.rotation);
             }
         }
 
-        let final_distance = (chain.joints.last().unwrap().position - target).length();
-        SolveResult {
-            converged: final_distance <= tolerance,
-            iterations: max_iterations,
-            final_distance,
+        trace.collided = false;
+        if let Some((world, config)) = collision {
+            trace.collided = CollisionResponse::resolve_chain(chain, world, config);
+            trace.clamps += Self::backward_pass(chain, base, base_frame, goal.rotation);
         }
     }
 
-    fn forward_pass(chain: &mut Chain, target: Vec3) {
+    /// Rotates the intermediate joints rigidly about the base-to-end axis so that the
+    /// chain's bend points towards `pole`. Bone lengths and both ends are unchanged.
+    ///
+    /// For pose goals the last bone is already fixed by the goal rotation, so the axis
+    /// ends at the joint before the end effector instead.
+    fn bend_towards_pole(chain: &mut Chain, pole: Vec3, goal: Goal) -> bool {
+        let Some(last) = Self::pole_bend_end(chain, goal) else {
+            return false;
+        };
+
+        let base = chain.joints[0].position;
+        let axis = (chain.joints[last].position - base).normalize_or_zero();
+        if axis == Vec3::ZERO {
+            return false;
+        }
+
+        let project = |point: Vec3| {
+            let offset = point - base;
+            offset - axis * offset.dot(axis)
+        };
+        let bend: Vec3 = chain.joints[1..last]
+            .iter()
+            .map(|j| project(j.position))
+            .sum();
+        let pole_direction = project(pole);
+        if bend.length_squared() < 1e-8 || pole_direction.length_squared() < 1e-8 {
+            return false;
+        }
+
+        let angle = axis
+            .dot(bend.cross(pole_direction))
+            .atan2(bend.dot(pole_direction));
+        let rotation = Quat::from_axis_angle(axis, angle);
+        for joint in &mut chain.joints[1..last] {
+            joint.position = base + rotation * (joint.position - base);
+        }
+        true
+    }
+
+    /// Joint at the far end of the axis the pole bends the chain about, or `None` if
+    /// there are no free joints in between to bend.
+    fn pole_bend_end(chain: &Chain, goal: Goal) -> Option<usize> {
         let n = chain.joints.len();
+        let last = if goal.rotation.is_some() {
+            n - 2
+        } else {
+            n - 1
+        };
+        (last >= 2 && !chain.joints[1..last].iter().any(Joint::is_pinned)).then_some(last)
+    }
 
-        chain.joints[n - 1].position = target;
+    /// Pushes the middle joints of a chain lying along the base-to-target axis towards
+    /// the pole. FABRIK keeps a straight chain straight, and [`Self::bend_towards_pole`]
+    /// has no bend to rotate, so without this it would never fold.
+    fn seed_pole_bend(chain: &mut Chain, pole: Vec3, goal: Goal, base: Vec3) {
+        let Some(last) = Self::pole_bend_end(chain, goal) else {
+            return;
+        };
+        let axis = (goal.position - base).normalize_or_zero();
+        if axis == Vec3::ZERO {
+            return;
+        }
+
+        let project = |point: Vec3| {
+            let offset = point - base;
+            offset - axis * offset.dot(axis)
+        };
+        let total_length = chain.total_length();
+        let straight = chain.joints[1..last]
+            .iter()
+            .all(|j| project(j.position).length() <= 1e-4 * total_length);
+        if !straight {
+            return;
+        }
+        let Some(side) = project(pole).try_normalize() else {
+            return;
+        };
+
+        let push = side * POLE_SEED_BEND * total_length;
+        for joint in &mut chain.joints[1..last] {
+            joint.position += push;
+        }
+    }
+
+    /// Distance of the end effector from the goal, or the largest amount a bone leading
+    /// into a pinned joint is stretched or squashed if that is worse.
+    fn goal_distance(chain: &Chain, goal: Goal) -> f32 {
+        let end_distance = chain
+            .joints
+            .last()
+            .map_or(0.0, |end| (end.position - goal.position).length());
+
+        chain
+            .joints
+            .windows(2)
+            .enumerate()
+            .filter(|(_, pair)| pair[1].is_pinned())
+            .map(|(bone, pair)| {
+                ((pair[1].position - pair[0].position).length() - chain.bone_length(bone)).abs()
+            })
+            .fold(end_distance, f32::max)
+    }
+
+    fn orientation_error(chain: &Chain, goal: Goal) -> f32 {
+        let (Some(rotation), Some(last_bone)) = (goal.rotation, chain.joints.iter().rev().nth(1))
+        else {
+            return 0.0;
+        };
+        last_bone.world_frame.rotation.angle_between(rotation)
+    }
+
+    /// Multi-end-effector FABRIK over a [`Skeleton`].
+    ///
+    /// `targets` is indexed by branch. Each branch with a target is pulled towards it in
+    /// the forward stage; a branch with driven children is pulled towards the centroid of
+    /// its children's sub-base positions instead. The backward stage then re-anchors the
+    /// root and solves every child from its parent's end joint.
+    pub fn solve_skeleton(skeleton: &mut Skeleton, targets: &[Option<Vec3>]) -> SolveResult {
+        let result = Self::solve_skeleton_positions(skeleton, targets);
+        for branch in skeleton.branches.iter_mut() {
+            branch.chain.update_frames();
+        }
+        result
+    }
+
+    fn solve_skeleton_positions(skeleton: &mut Skeleton, targets: &[Option<Vec3>]) -> SolveResult {
+        let branch_count = skeleton.branches.len();
+        if targets.iter().flatten().any(|t| !t.is_finite()) {
+            return SolveResult::rejected();
+        }
+        let Some(root) = skeleton.root() else {
+            return SolveResult::trivial();
+        };
+
+        let tolerance = skeleton.tolerance;
+        let target_of = |branch: usize| targets.get(branch).copied().flatten();
+        let mut trace = SolveTrace::with_settings(
+            tolerance,
+            skeleton.stall_threshold,
+            skeleton.record_error_history,
+        );
+        // Branches keep the bone lengths they were built with; stretch left over from
+        // single-chain solves doesn't carry into the skeleton.
+        for branch in skeleton.branches.iter_mut() {
+            branch.chain.bone_scales.fill(1.0);
+        }
+
+        for _ in 0..skeleton.max_iterations {
+            let mut sub_targets: Vec<Option<Vec3>> = (0..branch_count).map(target_of).collect();
+            let mut child_sums = vec![(Vec3::ZERO, 0u32); branch_count];
+
+            for b in (0..branch_count).rev() {
+                let (sum, count) = child_sums[b];
+                if count > 0 {
+                    let centroid = sum / count as f32;
+                    sub_targets[b] = Some(match sub_targets[b] {
+                        Some(own) => (own + sum) / (count + 1) as f32,
+                        None => centroid,
+                    });
+                }
+
+                let branch = &mut skeleton.branches[b];
+                if branch.chain.joints.len() < 2 {
+                    continue;
+                }
+                let Some(sub_target) = sub_targets[b] else {
+                    continue;
+                };
+
+                let base_frame = branch.chain.base_frame();
+                trace.clamps +=
+                    Self::forward_pass(&mut branch.chain, Goal::position(sub_target), base_frame);
+
+                if let Some(parent) = branch.parent {
+                    let sub_base = branch.chain.joints[0].position;
+                    child_sums[parent].0 += sub_base;
+                    child_sums[parent].1 += 1;
+                }
+            }
+
+            for b in 0..branch_count {
+                let (base, base_frame) = match skeleton.branches[b].parent {
+                    Some(parent) => match skeleton.branches[parent].chain.joints.last() {
+                        Some(end) => (end.position, end.world_frame.rotation),
+                        None => continue,
+                    },
+                    None => (root, skeleton.branches[b].chain.base_frame()),
+                };
+
+                let chain = &mut skeleton.branches[b].chain;
+                if chain.joints.len() < 2 {
+                    continue;
+                }
+                trace.clamps += Self::backward_pass(chain, base, base_frame, None);
+            }
+
+            let distance = Self::skeleton_distance(skeleton, targets);
+            trace.record(distance);
+            if distance <= tolerance || trace.is_stalled() {
+                break;
+            }
+        }
+
+        let final_distance = Self::skeleton_distance(skeleton, targets);
+        let termination = if final_distance <= tolerance {
+            Termination::Converged
+        } else {
+            trace.unconverged()
+        };
+        trace.into_result(final_distance, 0.0, termination, Vec::new())
+    }
+
+    fn skeleton_distance(skeleton: &Skeleton, targets: &[Option<Vec3>]) -> f32 {
+        skeleton
+            .branches
+            .iter()
+            .zip(targets)
+            .filter_map(|(branch, target)| {
+                Some((branch.chain.end_effector()? - (*target)?).length())
+            })
+            .fold(0.0, f32::max)
+    }
+
+    /// Places joints from the end effector back towards the base.
+    ///
+    /// Constraints are defined on the outgoing bone of a joint relative to its incoming
+    /// bone. Walking backwards the outgoing bone is already fixed, so a violation is
+    /// corrected by rotating the incoming bone instead, which yields the same relative
+    /// rotation as clamping the outgoing one.
+    ///
+    /// For pose goals the last bone is laid down along the goal rotation before walking
+    /// back, which is the FABRIK paper's extra "virtual joint" for end effector orientation.
+    ///
+    /// Joints with a target are aimed at it, and pinned joints are put back on their pin
+    /// so the rest of the pass continues from there as a sub-target.
+    fn forward_pass(chain: &mut Chain, goal: Goal, base_frame: Quat) -> Clamps {
+        let n = chain.joints.len();
+        let up = chain.up_vector;
+        let mut clamps = Clamps::default();
+
+        chain.joints[n - 1].position = goal.position;
 
         for i in (0..n - 1).rev() {
             let next_pos = chain.joints[i + 1].position;
-            let curr_pos = chain.joints[i].position;
-            let bone_length = chain.bone_lengths[i];
-
-            let dir = curr_pos - next_pos;
-            let len = dir.length();
+            let curr_pos = match i {
+                0 => chain.joints[0].position,
+                _ => chain.joints[i].aim(chain.joints[i].position),
+            };
+            let bone_length = chain.bone_length(i);
 
-            let direction = if len > 0.0001 {
-                dir / len
-            } else {
-                Vec3::Y
+            let direction = Self::direction_or_up(next_pos - curr_pos);
+            let mut rotation = match goal.rotation {
+                Some(end_rotation) if i + 2 == n => end_rotation,
+                _ => frame::align(chain.joints[i].world_frame.rotation, direction, up),
             };
 
-            chain.joints[i].position = next_pos + direction * bone_length;
+            if i + 2 < n {
+                let child = &chain.joints[i + 1];
+                if let Some(constraint) = &child.constraint {
+                    let child_rotation = child.world_frame.rotation;
+                    let allowed = constraint.apply_rotation(child_rotation, rotation);
+                    if clamps.record(child_rotation, allowed) {
+                        let correction = allowed * child_rotation.inverse();
+                        rotation = (correction.inverse() * rotation).normalize();
+                    }
+                }
+            }
+
+            if i == 0 {
+                rotation = Self::constrain(&chain.joints[0], rotation, base_frame, &mut clamps);
+            }
+
+            let joint = &mut chain.joints[i];
+            joint.position = match joint.target {
+                Some(target) if i > 0 && target.is_pinned() => target.position,
+                _ => next_pos - rotation * Vec3::Y * bone_length,
+            };
+            joint.world_frame.rotation = rotation;
         }
+
+        clamps
     }
 
-    fn backward_pass(chain: &mut Chain, base: Vec3) {
+    /// Places joints from the base out towards the end effector, clamping each bone
+    /// against its parent bone's frame (or `base_frame` for the first bone).
+    ///
+    /// Bone rotations are propagated the same way as [`Chain::update_frames`], so
+    /// frame-dependent constraints see the parent frame the caller will end up with.
+    /// With an `end_rotation` the last bone is laid along it rather than towards the old
+    /// end joint, so pose goals only have to be chased by the bones before it. Mid-chain
+    /// targets are handled as in [`Self::forward_pass`], with pinned joints acting as
+    /// sub-bases for the bones after them.
+    fn backward_pass(
+        chain: &mut Chain,
+        base: Vec3,
+        base_frame: Quat,
+        end_rotation: Option<Quat>,
+    ) -> Clamps {
         let n = chain.joints.len();
+        let up = chain.up_vector;
+        let mut clamps = Clamps::default();
 
         chain.joints[0].position = base;
+        let mut parent_frame = base_frame;
 
         for i in 1..n {
             let prev_pos = chain.joints[i - 1].position;
-            let curr_pos = chain.joints[i].position;
-            let bone_length = chain.bone_lengths[i - 1];
-
-            let dir = curr_pos - prev_pos;
-            let len = dir.length();
+            let curr_pos = match i == n - 1 {
+                true => chain.joints[i].position,
+                false => chain.joints[i].aim(chain.joints[i].position),
+            };
+            let bone_length = chain.bone_length(i - 1);
+
+            let joint = &chain.joints[i - 1];
+            let candidate = match end_rotation {
+                Some(end_rotation) if i == n - 1 => end_rotation,
+                _ => {
+                    let seed = if i == 1 {
+                        joint.world_frame.rotation
+                    } else {
+                        joint.frame_seed(parent_frame)
+                    };
+                    frame::align(seed, Self::direction_or_up(curr_pos - prev_pos), up)
+                }
+            };
+            let rotation = Self::constrain(joint, candidate, parent_frame, &mut clamps);
 
-            let direction = if len > 0.0001 {
-                dir / len
-            } else {
-                Vec3::Y
+            chain.joints[i - 1].world_frame.rotation = rotation;
+            chain.joints[i].position = match chain.joints[i].target {
+                Some(target) if i < n - 1 && target.is_pinned() => target.position,
+                _ => prev_pos + rotation * Vec3::Y * bone_length,
             };
+            parent_frame = rotation;
+        }
 
-            chain.joints[i].position = prev_pos + direction * bone_length;
+        chain.joints[n - 1].world_frame.rotation = parent_frame;
+        clamps
+    }
+
+    pub(crate) fn constrain(
+        joint: &Joint,
+        rotation: Quat,
+        parent_frame: Quat,
+        clamps: &mut Clamps,
+    ) -> Quat {
+        let Some(constraint) = &joint.constraint else {
+            return rotation;
+        };
+
+        let constrained = constraint.apply_rotation(rotation, parent_frame);
+        if !constrained.is_finite() {
+            return rotation;
         }
+
+        clamps.record(rotation, constrained);
+        constrained
     }
 
-    fn stretch_towards_target(chain: &mut Chain, base: Vec3, target: Vec3) {
+    fn direction_or_up(v: Vec3) -> Vec3 {
+        let len = v.length();
+        if len > 0.0001 {
+            v / len
+        } else {
+            Vec3::Y
+        }
+    }
+
+    pub(crate) fn stretch_towards_target(chain: &mut Chain, base: Vec3, target: Vec3) {
         let direction = (target - base).normalize_or_zero();
 
         if direction.length_squared() < 0.0001 {
@@ -132,7 +842,7 @@ impl FabrikSolver {
 
         for i in 1..chain.joints.len() {
             let prev = chain.joints[i - 1].position;
-            let bone_length = chain.bone_lengths[i - 1];
+            let bone_length = chain.bone_length(i - 1);
             chain.joints[i].position = prev + direction * bone_length;
         }
     }
@@ -143,16 +853,9 @@ impl FabrikSolver {
         world: &ObstacleWorld,
         config: &CollisionConfig,
     ) -> SolveResult {
-        if chain.joints.is_empty() {
-            return SolveResult {
-                converged: true,
-                iterations: 0,
-                final_distance: 0.0,
-            };
-        }
-
-        let base = chain.joints[0].position;
-        Self::solve_anchored_with_collision(chain, target, base, world, config)
+        let result = Self::solve_rooted(chain, Goal::position(target), Some((world, config)));
+        chain.update_frames();
+        result
     }
 
     pub fn solve_anchored_with_collision(
@@ -162,74 +865,98 @@ impl FabrikSolver {
         world: &ObstacleWorld,
         config: &CollisionConfig,
     ) -> SolveResult {
-        let joint_count = chain.joints.len();
-
-        if joint_count < 2 {
-            return SolveResult {
-                converged: true,
-                iterations: 0,
-                final_distance: 0.0,
-            };
-        }
-
-        let total_length = chain.total_length();
-        let distance_to_target = (target - base).length();
-
-        if distance_to_target > total_length {
-            Self::stretch_towards_target(chain, base, target);
-            CollisionResponse::resolve_chain(chain, world, config);
-            return SolveResult {
-                converged: false,
-                iterations: 1,
-                final_distance: distance_to_target - total_length,
-            };
-        }
-
-        let tolerance = chain.tolerance;
-        let max_iterations = chain.max_iterations;
+        let result = Self::solve_goal(chain, Goal::position(target), base, Some((world, config)));
+        chain.update_frames();
+        result
+    }
+}
 
-        for iteration in 0..max_iterations {
-            Self::forward_pass(chain, target);
-            Self::backward_pass(chain, base);
+impl IkSolver for FabrikSolver {
+    fn solve(&self, chain: &mut Chain, target: Vec3) -> SolveResult {
+        FabrikSolver::solve(chain, target)
+    }
 
-            CollisionResponse::resolve_chain(chain, world, config);
+    fn solve_with_collision(
+        &self,
+        chain: &mut Chain,
+        target: Vec3,
+        world: &ObstacleWorld,
+        config: &CollisionConfig,
+    ) -> SolveResult {
+        FabrikSolver::solve_with_collision(chain, target, world, config)
+    }
+}
 
-            chain.joints[0].position = base;
-            Self::fix_bone_lengths_forward(chain);
+pub(crate) fn check_target(target: Vec3) -> Result<(), ChainError> {
+    if target.is_finite() {
+        Ok(())
+    } else {
+        Err(ChainError::NonFiniteTarget)
+    }
+}
 
-            let end_effector = chain.joints.last().unwrap().position;
-            let distance = (end_effector - target).length();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ik::HingeConstraint;
+
+    /// Straight two-bone arm whose elbow only bends towards -X.
+    fn one_sided_elbow() -> Chain {
+        Chain::builder()
+            .add_joint(Vec3::ZERO)
+            .add_joint_with_constraint(Vec3::Y, HingeConstraint::new(Vec3::Z, 0.0, 120.0))
+            .add_joint(Vec3::Y * 2.0)
+            .max_iterations(100)
+            .build()
+    }
 
-            if distance <= tolerance {
-                return SolveResult {
-                    converged: true,
-                    iterations: iteration + 1,
-                    final_distance: distance,
-                };
-            }
+    #[test]
+    fn one_sided_hinge_reaches_targets_on_its_locked_side() {
+        for target in [
+            Vec3::new(1.0, 1.0, 0.0),
+            Vec3::new(0.0, 1.0, 1.0),
+            Vec3::new(1.0, 1.0, 0.5),
+        ] {
+            let mut chain = one_sided_elbow();
+            let result = FabrikSolver::solve(&mut chain, target);
+            assert_eq!(
+                result.termination,
+                Termination::Converged,
+                "target {target}"
+            );
         }
 
-        let final_distance = (chain.joints.last().unwrap().position - target).length();
-        SolveResult {
-            converged: final_distance <= tolerance,
-            iterations: max_iterations,
-            final_distance,
+        for target in [Vec3::new(1.0, 1.0, 0.0), Vec3::new(1.0, 1.0, 0.5)] {
+            let mut chain = one_sided_elbow();
+            let result = CcdSolver.solve(&mut chain, target);
+            assert_eq!(
+                result.termination,
+                Termination::Converged,
+                "target {target}"
+            );
         }
     }
 
-    fn fix_bone_lengths_forward(chain: &mut Chain) {
-        let n = chain.joints.len();
-        for i in 1..n {
-            let prev_pos = chain.joints[i - 1].position;
-            let curr_pos = chain.joints[i].position;
-            let bone_length = chain.bone_lengths[i - 1];
-
-            let dir = curr_pos - prev_pos;
-            let len = dir.length();
-
-            let direction = if len > 0.0001 { dir / len } else { Vec3::Y };
-
-            chain.joints[i].position = prev_pos + direction * bone_length;
+    #[test]
+    fn free_root_converges_on_targets_beyond_reach() {
+        for target in [Vec3::new(0.0, 4.0, 0.0), Vec3::new(0.3, 4.0, 0.0)] {
+            let mut chain = Chain::builder()
+                .add_joint(Vec3::ZERO)
+                .add_joint(Vec3::Y)
+                .add_joint(Vec3::Y * 2.0)
+                .root_mode(RootMode::Free)
+                .build();
+            let result = FabrikSolver::solve(&mut chain, target);
+            assert_eq!(
+                result.termination,
+                Termination::Converged,
+                "target {target}"
+            );
+            let moved = result.root_displacement.length();
+            assert!(
+                (moved - (target.length() - 2.0)).abs() < 1e-4,
+                "root moved {moved}"
+            );
         }
     }
 }
@@ -0,0 +1,116 @@
+use super::chain::Chain;
+use super::solver::{FabrikSolver, Goal, IkSolver, SolveResult, SolveTrace};
+use crate::collision::{CollisionConfig, ObstacleWorld};
+use glam::Vec3;
+
+/// Closed-form solver for three-joint limbs (shoulder, elbow, wrist or hip, knee, ankle).
+///
+/// The elbow is placed with the law of cosines in the plane spanned by the base-to-target
+/// axis and a bend direction: towards the chain's pole target when it has one, otherwise
+/// the side the elbow is currently bent to. The result only depends on the target and
+/// the hint, so it is stable frame to frame.
+///
+/// Chains that do not have exactly three joints are handed to [`FabrikSolver`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TwoBoneSolver;
+
+impl TwoBoneSolver {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn solve_inner(
+        chain: &mut Chain,
+        target: Vec3,
+        collision: Option<(&ObstacleWorld, &CollisionConfig)>,
+    ) -> SolveResult {
+        if chain.joints.len() != 3 {
+            return match collision {
+                Some((world, config)) => {
+                    FabrikSolver::solve_with_collision(chain, target, world, config)
+                }
+                None => FabrikSolver::solve(chain, target),
+            };
+        }
+
+        if !target.is_finite() {
+            return SolveResult::rejected();
+        }
+
+        let base = chain.joints[0].position;
+        chain.fit_stretch(base, target);
+        let base_frame = chain.base_frame();
+        let total_length = chain.total_length();
+        let distance_to_target = (target - base).length();
+
+        if distance_to_target > total_length {
+            FabrikSolver::stretch_towards_target(chain, base, target);
+        } else {
+            Self::place(chain, base, target);
+        }
+
+        let goal = Goal::position(target);
+        let mut trace = SolveTrace::new(chain);
+        FabrikSolver::settle(chain, goal, base, base_frame, collision, &mut trace);
+        chain.update_frames();
+
+        trace.record((chain.joints[2].position - target).length());
+        trace.finish(chain, goal, base)
+    }
+
+    fn place(chain: &mut Chain, base: Vec3, target: Vec3) {
+        let upper = chain.bone_length(0);
+        let lower = chain.bone_length(1);
+
+        let axis = (target - base)
+            .try_normalize()
+            .or_else(|| (chain.joints[2].position - base).try_normalize())
+            .unwrap_or(chain.base_direction);
+        let distance = (target - base)
+            .length()
+            .clamp((upper - lower).abs(), upper + lower);
+
+        let hint = chain
+            .pole_target
+            .map_or(chain.joints[1].position, |pole| pole)
+            - base;
+        let bend = (hint - axis * hint.dot(axis))
+            .try_normalize()
+            .or_else(|| {
+                let up = chain.up_vector;
+                (up - axis * up.dot(axis)).try_normalize()
+            })
+            .unwrap_or_else(|| axis.any_orthonormal_vector());
+
+        let cos_base = if distance > 0.0 {
+            ((upper * upper + distance * distance - lower * lower) / (2.0 * upper * distance))
+                .clamp(-1.0, 1.0)
+        } else {
+            1.0
+        };
+        let sin_base = (1.0 - cos_base * cos_base).sqrt();
+
+        let elbow = base + (axis * cos_base + bend * sin_base) * upper;
+        let wrist = base + axis * distance;
+
+        chain.joints[0].position = base;
+        chain.joints[1].position = elbow;
+        chain.joints[2].position = elbow + (wrist - elbow).normalize_or_zero() * lower;
+    }
+}
+
+impl IkSolver for TwoBoneSolver {
+    fn solve(&self, chain: &mut Chain, target: Vec3) -> SolveResult {
+        Self::solve_inner(chain, target, None)
+    }
+
+    fn solve_with_collision(
+        &self,
+        chain: &mut Chain,
+        target: Vec3,
+        world: &ObstacleWorld,
+        config: &CollisionConfig,
+    ) -> SolveResult {
+        Self::solve_inner(chain, target, Some((world, config)))
+    }
+}
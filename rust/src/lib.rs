@@ -4,8 +4,12 @@
 //! with WebGPU rendering support.
 //!
 //! ## Features
-//! - FABRIK (Forward And Backward Reaching Inverse Kinematics) solver
-//! - Constraint system (ball-socket, extensible via traits)
+//! - FABRIK (Forward And Backward Reaching Inverse Kinematics) solver with position and pose targets
+//! - CCD, damped least squares Jacobian and analytic two-bone solvers behind a common
+//!   `IkSolver` trait
+//! - Forward kinematics from per-joint local frames, joint angle extraction and IK/FK
+//!   blending
+//! - Constraint system (ball-socket, hinge, elliptical cone, swing-twist, extensible via traits)
 //! - WebGPU-based debug visualization
 //! - Cross-platform: Native + WASM support
 //!
@@ -39,10 +43,18 @@ pub mod render;
 pub mod web;
 
 pub use collision::{
-    AabbObstacle, CollisionConfig, CollisionHit, CollisionResponse, Obstacle, ObstacleShape,
-    ObstacleWorld, Ray, RayHit, SphereObstacle,
+    AabbObstacle, CapsuleObstacle, CollisionConfig, CollisionHit, CollisionResponse,
+    ConvexHullObstacle, CylinderObstacle, MeshError, MeshObstacle, Obstacle, ObstacleShape,
+    ObstacleWorld, OrientedBoxObstacle, PlaneObstacle, Ray, RayHit, SdfNode, SegmentContact, SphereObstacle,
 };
 pub use dynamics::{Interpolatable, SecondOrderDynamics, SpringPreset};
-pub use ik::constraint::{BallSocketConstraint, Constraint};
-pub use ik::{Chain, ChainBuilder, FabrikSolver, Joint, SolveResult};
+pub use ik::constraint::{
+    BallSocketConstraint, Constraint, EllipticalConeConstraint, HingeConstraint,
+    SwingTwistConstraint,
+};
+pub use ik::{
+    Branch, CcdSolver, Chain, ChainBuilder, ChainError, FabrikSolver, IkFkBlend, IkSolver,
+    JacobianSolver, Joint, JointTarget, LengthRange, RootMode, Skeleton, SkeletonBuilder,
+    SolveResult, Termination, TwoBoneSolver,
+};
 pub use math::Transform;
@@ -107,22 +107,21 @@ impl ApplicationHandler for App<'_> {
             WindowEvent::CloseRequested => {
                 event_loop.exit();
             }
-            WindowEvent::KeyboardInput { event, .. } => {
-                if event.state == ElementState::Pressed {
-                    if let PhysicalKey::Code(code) = event.physical_key {
-                        match code {
-                            KeyCode::Escape => event_loop.exit(),
-                            KeyCode::ArrowUp => self.target.y += 0.1,
-                            KeyCode::ArrowDown => self.target.y -= 0.1,
-                            KeyCode::ArrowLeft => self.target.x -= 0.1,
-                            KeyCode::ArrowRight => self.target.x += 0.1,
-                            KeyCode::KeyW => self.target.z -= 0.1,
-                            KeyCode::KeyS => self.target.z += 0.1,
-                            KeyCode::KeyR => {
-                                self.target = Vec3::new(2.0, 2.0, 0.0);
-                            }
-                            _ => {}
+            WindowEvent::KeyboardInput { event, .. } if event.state == ElementState::Pressed => {
+                if let PhysicalKey::Code(code) = event.physical_key {
+                    match code {
+                        KeyCode::Escape => event_loop.exit(),
+                        KeyCode::ArrowUp => self.target.y += 0.1,
+                        KeyCode::ArrowDown => self.target.y -= 0.1,
+                        KeyCode::ArrowLeft => self.target.x -= 0.1,
+                        KeyCode::ArrowRight => self.target.x += 0.1,
+                        KeyCode::KeyW => self.target.z -= 0.1,
+                        KeyCode::KeyS => self.target.z += 0.1,
+                        KeyCode::KeyR => {
+                            self.target = Vec3::new(2.0, 2.0, 0.0);
+                            self.chain.reset_to_rest();
                         }
+                        _ => {}
                     }
                 }
             }
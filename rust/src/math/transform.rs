@@ -84,6 +84,30 @@ impl Transform {
         }
     }
 
+    /// The transform that undoes this one. Exact for uniform scale.
+    pub fn inverse(&self) -> Self {
+        let rotation = self.rotation.inverse();
+        let scale = self.scale.recip();
+        Self {
+            position: rotation * -self.position * scale,
+            rotation,
+            scale,
+        }
+    }
+
+    /// Composes `self` with `child`, a transform expressed in `self`'s space.
+    pub fn mul_transform(&self, child: &Self) -> Self {
+        Self {
+            position: self.transform_point(child.position),
+            rotation: self.rotation * child.rotation,
+            scale: self.scale * child.scale,
+        }
+    }
+
+    pub fn inverse_transform_point(&self, point: Vec3) -> Vec3 {
+        self.rotation.inverse() * (point - self.position) / self.scale
+    }
+
     pub fn lerp(&self, other: &Self, t: f32) -> Self {
         Self {
             position: self.position.lerp(other.position, t),
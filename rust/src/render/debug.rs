@@ -1,13 +1,36 @@
 use super::camera::Camera;
 use super::context::GpuContext;
-use super::mesh::Mesh;
+use super::mesh::{Mesh, Vertex};
 
 use super::pipeline::{RenderPipelines, Uniforms};
 use crate::collision::{CollisionHit, ObstacleShape, ObstacleWorld};
-use crate::ik::Chain;
-use glam::{Mat4, Vec3};
+use crate::ik::{Chain, Skeleton};
+use glam::{Mat4, Quat, Vec3};
+use std::cell::RefCell;
+
+const MAX_INSTANCES: usize = 128;
+
+/// Half the side of the grid square drawn for a plane obstacle.
+const PLANE_DRAW_HALF_SIZE: f32 = 5.0;
+
+#[derive(Clone, Copy)]
+enum MeshType {
+    Sphere,
+    Cylinder,
+    WireframeSphere,
+    WireframeBox,
+    WireframeCylinder,
+    WireframeGrid,
+    /// Outline `mesh` of obstacle `obstacle`, kept in [`DebugRenderer`]'s cache.
+    ObstacleLines { obstacle: usize, mesh: usize },
+}
 
-const MAX_INSTANCES: usize = 64;
+/// Outlines of one obstacle's hulls and triangle meshes, built for the obstacle state
+/// `stamp`.
+struct ObstacleWireframe {
+    stamp: u64,
+    meshes: Vec<Mesh>,
+}
 
 pub struct DebugRenderer {
     pipelines: RenderPipelines,
@@ -16,6 +39,11 @@ pub struct DebugRenderer {
     cylinder_mesh: Mesh,
     wireframe_sphere_mesh: Mesh,
     wireframe_box_mesh: Mesh,
+    wireframe_cylinder_mesh: Mesh,
+    wireframe_grid_mesh: Mesh,
+    /// Indexed like the obstacles of the last world drawn. Entries are rebuilt when the
+    /// obstacle's stamp changes, so static meshes are uploaded once.
+    obstacle_wireframes: RefCell<Vec<Option<ObstacleWireframe>>>,
     uniform_buffer: wgpu::Buffer,
     bind_group: wgpu::BindGroup,
     uniform_alignment: u32,
@@ -29,6 +57,8 @@ impl DebugRenderer {
         let cylinder_mesh = Mesh::cylinder(&context.device, 1.0, 1.0, 12);
         let wireframe_sphere_mesh = Mesh::wireframe_sphere(&context.device, 1.0, 16, 8);
         let wireframe_box_mesh = Mesh::wireframe_box(&context.device, Vec3::ONE);
+        let wireframe_cylinder_mesh = Mesh::wireframe_cylinder(&context.device, 1.0, 1.0, 16);
+        let wireframe_grid_mesh = Mesh::wireframe_grid(&context.device, 1.0, 10);
 
         let uniform_alignment = context.device.limits().min_uniform_buffer_offset_alignment;
         let aligned_size = Self::align_to(std::mem::size_of::<Uniforms>() as u32, uniform_alignment);
@@ -50,6 +80,9 @@ impl DebugRenderer {
             cylinder_mesh,
             wireframe_sphere_mesh,
             wireframe_box_mesh,
+            wireframe_cylinder_mesh,
+            wireframe_grid_mesh,
+            obstacle_wireframes: RefCell::new(Vec::new()),
             uniform_buffer,
             bind_group,
             uniform_alignment,
@@ -64,6 +97,157 @@ impl DebugRenderer {
         Self::align_to(std::mem::size_of::<Uniforms>() as u32, self.uniform_alignment)
     }
 
+    fn write_instance(
+        uniform_data: &mut [u8],
+        offset: usize,
+        view_proj: Mat4,
+        model: Mat4,
+        color: [f32; 4],
+    ) {
+        let uniforms = Uniforms {
+            view_proj: view_proj.to_cols_array_2d(),
+            model: model.to_cols_array_2d(),
+            color,
+        };
+        let bytes = bytemuck::bytes_of(&uniforms);
+        uniform_data[offset..offset + bytes.len()].copy_from_slice(bytes);
+    }
+
+    /// Wireframe instances that draw obstacle `obstacle`. Hull and mesh outlines are
+    /// numbered from `next_outline` in the order [`Self::outline_meshes`] builds them.
+    fn obstacle_instances(
+        obstacle: usize,
+        shape: ObstacleShape,
+        next_outline: &mut usize,
+    ) -> Vec<(Mat4, MeshType, [f32; 4])> {
+        let mut outline = |color| {
+            let mesh = *next_outline;
+            *next_outline += 1;
+            vec![(Mat4::IDENTITY, MeshType::ObstacleLines { obstacle, mesh }, color)]
+        };
+        match shape {
+            ObstacleShape::Sphere { center, radius } => vec![(
+                Mat4::from_translation(center) * Mat4::from_scale(Vec3::splat(radius)),
+                MeshType::WireframeSphere,
+                [1.0, 0.5, 0.0, 1.0],
+            )],
+            ObstacleShape::Box { center, half_extents } => vec![(
+                Mat4::from_translation(center) * Mat4::from_scale(half_extents),
+                MeshType::WireframeBox,
+                [0.0, 1.0, 0.5, 1.0],
+            )],
+            ObstacleShape::Capsule { start, end, radius } => {
+                let color = [1.0, 0.7, 0.2, 1.0];
+                let cap = Mat4::from_scale(Vec3::splat(radius));
+                vec![
+                    (
+                        Mesh::create_bone_transform(start, end) * Mat4::from_scale(Vec3::new(radius, 1.0, radius)),
+                        MeshType::WireframeCylinder,
+                        color,
+                    ),
+                    (Mat4::from_translation(start) * cap, MeshType::WireframeSphere, color),
+                    (Mat4::from_translation(end) * cap, MeshType::WireframeSphere, color),
+                ]
+            }
+            ObstacleShape::OrientedBox { center, rotation, half_extents } => vec![(
+                Mat4::from_scale_rotation_translation(half_extents, rotation, center),
+                MeshType::WireframeBox,
+                [0.0, 1.0, 0.5, 1.0],
+            )],
+            ObstacleShape::Plane { normal, offset } => vec![(
+                Mat4::from_scale_rotation_translation(
+                    Vec3::splat(PLANE_DRAW_HALF_SIZE),
+                    Quat::from_rotation_arc(Vec3::Y, normal),
+                    normal * offset,
+                ),
+                MeshType::WireframeGrid,
+                [0.5, 0.5, 0.6, 1.0],
+            )],
+            ObstacleShape::Cylinder { center, axis, radius, half_height } => vec![(
+                Mat4::from_scale_rotation_translation(
+                    Vec3::new(radius, 2.0 * half_height, radius),
+                    Quat::from_rotation_arc(Vec3::Y, axis),
+                    center,
+                ),
+                MeshType::WireframeCylinder,
+                [1.0, 0.8, 0.0, 1.0],
+            )],
+            ObstacleShape::ConvexHull { .. } => outline([0.3, 0.8, 1.0, 1.0]),
+            ObstacleShape::Mesh { .. } => outline([0.7, 0.7, 0.8, 1.0]),
+            ObstacleShape::Group(shapes) => shapes
+                .into_iter()
+                .flat_map(|shape| Self::obstacle_instances(obstacle, shape, next_outline))
+                .collect(),
+        }
+    }
+
+    /// Makes `cached` hold the outlines of `shape`, unless it already does for `stamp`.
+    fn cache_outlines(
+        context: &GpuContext,
+        cached: &mut Option<ObstacleWireframe>,
+        stamp: u64,
+        shape: &ObstacleShape,
+    ) {
+        if matches!(cached, Some(wireframe) if wireframe.stamp == stamp) {
+            return;
+        }
+        let mut meshes = Vec::new();
+        Self::outline_meshes(context, shape, &mut meshes);
+        *cached = Some(ObstacleWireframe { stamp, meshes });
+    }
+
+    /// Line meshes for the hulls and triangle meshes in `shape`, in the order
+    /// [`Self::obstacle_instances`] numbers them.
+    fn outline_meshes(context: &GpuContext, shape: &ObstacleShape, meshes: &mut Vec<Mesh>) {
+        match shape {
+            ObstacleShape::ConvexHull { vertices, edges } => {
+                let indices: Vec<u32> = edges.iter().flatten().map(|&i| i as u32).collect();
+                meshes.push(Self::line_mesh(context, vertices, &indices));
+            }
+            ObstacleShape::Mesh { vertices, triangles } => {
+                let indices: Vec<u32> = triangles
+                    .iter()
+                    .flat_map(|&[a, b, c]| [a, b, b, c, c, a])
+                    .collect();
+                meshes.push(Self::line_mesh(context, vertices, &indices));
+            }
+            ObstacleShape::Group(shapes) => {
+                for shape in shapes {
+                    Self::outline_meshes(context, shape, meshes);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn line_mesh(context: &GpuContext, points: &[Vec3], indices: &[u32]) -> Mesh {
+        let vertices: Vec<Vertex> = points
+            .iter()
+            .map(|p| Vertex {
+                position: p.to_array(),
+                normal: [0.0, 1.0, 0.0],
+            })
+            .collect();
+        Mesh::from_line_data(&context.device, &vertices, indices)
+    }
+
+    fn wireframe_mesh<'a>(
+        &'a self,
+        mesh_type: MeshType,
+        obstacle_wireframes: &'a [Option<ObstacleWireframe>],
+    ) -> Option<&'a Mesh> {
+        match mesh_type {
+            MeshType::WireframeSphere => Some(&self.wireframe_sphere_mesh),
+            MeshType::WireframeBox => Some(&self.wireframe_box_mesh),
+            MeshType::WireframeCylinder => Some(&self.wireframe_cylinder_mesh),
+            MeshType::WireframeGrid => Some(&self.wireframe_grid_mesh),
+            MeshType::ObstacleLines { obstacle, mesh } => {
+                obstacle_wireframes.get(obstacle)?.as_ref()?.meshes.get(mesh)
+            }
+            MeshType::Sphere | MeshType::Cylinder => None,
+        }
+    }
+
     pub fn render(
         &self,
         context: &GpuContext,
@@ -231,14 +415,6 @@ impl DebugRenderer {
         let mut uniform_data = vec![0u8; aligned_size * MAX_INSTANCES];
         let mut instance_idx = 0;
 
-        #[derive(Clone, Copy)]
-        enum MeshType {
-            Sphere,
-            Cylinder,
-            WireframeSphere,
-            WireframeBox,
-        }
-
         struct DrawCall {
             mesh_type: MeshType,
             offset: u32,
@@ -325,39 +501,30 @@ impl DebugRenderer {
             instance_idx += 1;
         }
 
-        for obstacle in world.obstacles() {
+        let mut obstacle_wireframes = self.obstacle_wireframes.borrow_mut();
+        obstacle_wireframes.resize_with(world.obstacle_count(), || None);
+        for (o, obstacle) in world.obstacles().iter().enumerate() {
             if instance_idx >= MAX_INSTANCES {
                 break;
             }
 
-            let (model, mesh_type, color) = match obstacle.render_shape() {
-                ObstacleShape::Sphere { center, radius } => (
-                    Mat4::from_translation(center) * Mat4::from_scale(Vec3::splat(radius)),
-                    MeshType::WireframeSphere,
-                    [1.0, 0.5, 0.0, 1.0], 
-                ),
-                ObstacleShape::Box { center, half_extents } => (
-                    Mat4::from_translation(center) * Mat4::from_scale(half_extents),
-                    MeshType::WireframeBox,
-                    [0.0, 1.0, 0.5, 1.0], 
-                ),
-            };
-
-            let uniforms = Uniforms {
-                view_proj: view_proj.to_cols_array_2d(),
-                model: model.to_cols_array_2d(),
-                color,
-            };
+            let shape = obstacle.render_shape();
+            Self::cache_outlines(context, &mut obstacle_wireframes[o], world.stamp(o), &shape);
+            let instances = Self::obstacle_instances(o, shape, &mut 0);
+            for (model, mesh_type, color) in instances {
+                if instance_idx >= MAX_INSTANCES {
+                    break;
+                }
 
-            let offset = instance_idx * aligned_size;
-            let bytes = bytemuck::bytes_of(&uniforms);
-            uniform_data[offset..offset + bytes.len()].copy_from_slice(bytes);
+                let offset = instance_idx * aligned_size;
+                Self::write_instance(&mut uniform_data, offset, view_proj, model, color);
 
-            wireframe_draw_calls.push(DrawCall {
-                mesh_type,
-                offset: offset as u32,
-            });
-            instance_idx += 1;
+                wireframe_draw_calls.push(DrawCall {
+                    mesh_type,
+                    offset: offset as u32,
+                });
+                instance_idx += 1;
+            }
         }
 
         context.queue.write_buffer(&self.uniform_buffer, 0, &uniform_data);
@@ -415,18 +582,10 @@ impl DebugRenderer {
             render_pass.set_pipeline(&self.line_pipeline);
             for call in &wireframe_draw_calls {
                 render_pass.set_bind_group(0, &self.bind_group, &[call.offset]);
-                match call.mesh_type {
-                    MeshType::WireframeSphere => {
-                        render_pass.set_vertex_buffer(0, self.wireframe_sphere_mesh.vertex_buffer.slice(..));
-                        render_pass.set_index_buffer(self.wireframe_sphere_mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
-                        render_pass.draw_indexed(0..self.wireframe_sphere_mesh.index_count, 0, 0..1);
-                    }
-                    MeshType::WireframeBox => {
-                        render_pass.set_vertex_buffer(0, self.wireframe_box_mesh.vertex_buffer.slice(..));
-                        render_pass.set_index_buffer(self.wireframe_box_mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
-                        render_pass.draw_indexed(0..self.wireframe_box_mesh.index_count, 0, 0..1);
-                    }
-                    _ => {}
+                if let Some(mesh) = self.wireframe_mesh(call.mesh_type, &obstacle_wireframes) {
+                    render_pass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
+                    render_pass.set_index_buffer(mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+                    render_pass.draw_indexed(0..mesh.index_count, 0, 0..1);
                 }
             }
         }
@@ -434,6 +593,7 @@ impl DebugRenderer {
         context.queue.submit(std::iter::once(encoder.finish()));
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn render_with_collision_hits(
         &self,
         context: &GpuContext,
@@ -450,14 +610,6 @@ impl DebugRenderer {
         let mut uniform_data = vec![0u8; aligned_size * MAX_INSTANCES];
         let mut instance_idx = 0;
 
-        #[derive(Clone, Copy)]
-        enum MeshType {
-            Sphere,
-            Cylinder,
-            WireframeSphere,
-            WireframeBox,
-        }
-
         struct DrawCall {
             mesh_type: MeshType,
             offset: u32,
@@ -550,10 +702,14 @@ impl DebugRenderer {
             }
 
             let model = Mat4::from_translation(hit.surface_point) * Mat4::from_scale(Vec3::splat(0.06));
+            let color = match hit.bone {
+                Some(_) => [1.0, 1.0, 0.0, 1.0],
+                None => [1.0, 0.0, 1.0, 1.0],
+            };
             let uniforms = Uniforms {
                 view_proj: view_proj.to_cols_array_2d(),
                 model: model.to_cols_array_2d(),
-                color: [1.0, 0.0, 1.0, 1.0], 
+                color,
             };
 
             let offset = instance_idx * aligned_size;
@@ -567,39 +723,30 @@ impl DebugRenderer {
             instance_idx += 1;
         }
 
-        for obstacle in world.obstacles() {
+        let mut obstacle_wireframes = self.obstacle_wireframes.borrow_mut();
+        obstacle_wireframes.resize_with(world.obstacle_count(), || None);
+        for (o, obstacle) in world.obstacles().iter().enumerate() {
             if instance_idx >= MAX_INSTANCES {
                 break;
             }
 
-            let (model, mesh_type, color) = match obstacle.render_shape() {
-                ObstacleShape::Sphere { center, radius } => (
-                    Mat4::from_translation(center) * Mat4::from_scale(Vec3::splat(radius)),
-                    MeshType::WireframeSphere,
-                    [1.0, 0.5, 0.0, 1.0],  
-                ),
-                ObstacleShape::Box { center, half_extents } => (
-                    Mat4::from_translation(center) * Mat4::from_scale(half_extents),
-                    MeshType::WireframeBox,
-                    [0.0, 1.0, 0.5, 1.0], 
-                ),
-            };
-
-            let uniforms = Uniforms {
-                view_proj: view_proj.to_cols_array_2d(),
-                model: model.to_cols_array_2d(),
-                color,
-            };
+            let shape = obstacle.render_shape();
+            Self::cache_outlines(context, &mut obstacle_wireframes[o], world.stamp(o), &shape);
+            let instances = Self::obstacle_instances(o, shape, &mut 0);
+            for (model, mesh_type, color) in instances {
+                if instance_idx >= MAX_INSTANCES {
+                    break;
+                }
 
-            let offset = instance_idx * aligned_size;
-            let bytes = bytemuck::bytes_of(&uniforms);
-            uniform_data[offset..offset + bytes.len()].copy_from_slice(bytes);
+                let offset = instance_idx * aligned_size;
+                Self::write_instance(&mut uniform_data, offset, view_proj, model, color);
 
-            wireframe_draw_calls.push(DrawCall {
-                mesh_type,
-                offset: offset as u32,
-            });
-            instance_idx += 1;
+                wireframe_draw_calls.push(DrawCall {
+                    mesh_type,
+                    offset: offset as u32,
+                });
+                instance_idx += 1;
+            }
         }
 
         context.queue.write_buffer(&self.uniform_buffer, 0, &uniform_data);
@@ -657,22 +804,161 @@ impl DebugRenderer {
             render_pass.set_pipeline(&self.line_pipeline);
             for call in &wireframe_draw_calls {
                 render_pass.set_bind_group(0, &self.bind_group, &[call.offset]);
-                match call.mesh_type {
-                    MeshType::WireframeSphere => {
-                        render_pass.set_vertex_buffer(0, self.wireframe_sphere_mesh.vertex_buffer.slice(..));
-                        render_pass.set_index_buffer(self.wireframe_sphere_mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
-                        render_pass.draw_indexed(0..self.wireframe_sphere_mesh.index_count, 0, 0..1);
-                    }
-                    MeshType::WireframeBox => {
-                        render_pass.set_vertex_buffer(0, self.wireframe_box_mesh.vertex_buffer.slice(..));
-                        render_pass.set_index_buffer(self.wireframe_box_mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
-                        render_pass.draw_indexed(0..self.wireframe_box_mesh.index_count, 0, 0..1);
-                    }
-                    _ => {}
+                if let Some(mesh) = self.wireframe_mesh(call.mesh_type, &obstacle_wireframes) {
+                    render_pass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
+                    render_pass.set_index_buffer(mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+                    render_pass.draw_indexed(0..mesh.index_count, 0, 0..1);
                 }
             }
         }
 
         context.queue.submit(std::iter::once(encoder.finish()));
     }
-}
\ No newline at end of file
+
+    /// Draws every branch of `skeleton`. Sub-base joints (where branches split) are
+    /// highlighted, and each `Some` entry of `targets` is drawn for its branch.
+    pub fn render_skeleton(
+        &self,
+        context: &GpuContext,
+        view: &wgpu::TextureView,
+        skeleton: &Skeleton,
+        targets: &[Option<Vec3>],
+        camera: &Camera,
+    ) {
+        let view_proj = camera.view_projection();
+        let aligned_size = self.aligned_uniform_size() as usize;
+
+        let mut uniform_data = vec![0u8; aligned_size * MAX_INSTANCES];
+        let mut instance_idx = 0;
+
+        struct DrawCall {
+            is_sphere: bool,
+            offset: u32,
+        }
+        let mut draw_calls: Vec<DrawCall> = Vec::new();
+
+        for (b, branch) in skeleton.branches().iter().enumerate() {
+            let joints = branch.chain().joints();
+            let is_leaf = skeleton.is_leaf(b);
+
+            for (i, joint) in joints.iter().enumerate() {
+                if instance_idx >= MAX_INSTANCES {
+                    break;
+                }
+
+                let is_sub_base = i == 0 && branch.parent().is_some();
+                if is_sub_base {
+                    continue;
+                }
+
+                let is_root = i == 0;
+                let is_end = i == joints.len() - 1;
+                let color = if is_root {
+                    [0.2, 0.8, 0.2, 1.0]
+                } else if is_end && is_leaf {
+                    [0.8, 0.8, 0.2, 1.0]
+                } else if is_end {
+                    [0.9, 0.4, 0.9, 1.0]
+                } else {
+                    [0.3, 0.5, 0.9, 1.0]
+                };
+
+                let model = Mat4::from_translation(joint.position) * Mat4::from_scale(Vec3::splat(0.08));
+                let offset = instance_idx * aligned_size;
+                Self::write_instance(&mut uniform_data, offset, view_proj, model, color);
+
+                draw_calls.push(DrawCall {
+                    is_sphere: true,
+                    offset: offset as u32,
+                });
+                instance_idx += 1;
+            }
+
+            for i in 0..joints.len().saturating_sub(1) {
+                if instance_idx >= MAX_INSTANCES {
+                    break;
+                }
+
+                let start = joints[i].position;
+                let end = joints[i + 1].position;
+                let model = Mesh::create_bone_transform(start, end) * Mat4::from_scale(Vec3::new(0.03, 1.0, 0.03));
+                let offset = instance_idx * aligned_size;
+                Self::write_instance(&mut uniform_data, offset, view_proj, model, [0.6, 0.6, 0.7, 1.0]);
+
+                draw_calls.push(DrawCall {
+                    is_sphere: false,
+                    offset: offset as u32,
+                });
+                instance_idx += 1;
+            }
+        }
+
+        for target in targets.iter().flatten() {
+            if instance_idx >= MAX_INSTANCES {
+                break;
+            }
+
+            let model = Mat4::from_translation(*target) * Mat4::from_scale(Vec3::splat(0.12));
+            let offset = instance_idx * aligned_size;
+            Self::write_instance(&mut uniform_data, offset, view_proj, model, [1.0, 0.2, 0.2, 1.0]);
+
+            draw_calls.push(DrawCall {
+                is_sphere: true,
+                offset: offset as u32,
+            });
+            instance_idx += 1;
+        }
+
+        context.queue.write_buffer(&self.uniform_buffer, 0, &uniform_data);
+
+        let mut encoder = context.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Render Encoder"),
+        });
+
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Render Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color {
+                            r: 0.1,
+                            g: 0.1,
+                            b: 0.15,
+                            a: 1.0,
+                        }),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &context.depth_view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: wgpu::StoreOp::Store,
+                    }),
+                    stencil_ops: None,
+                }),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+
+            render_pass.set_pipeline(&self.pipelines.pipeline);
+
+            for call in &draw_calls {
+                render_pass.set_bind_group(0, &self.bind_group, &[call.offset]);
+
+                let mesh = if call.is_sphere {
+                    &self.sphere_mesh
+                } else {
+                    &self.cylinder_mesh
+                };
+                render_pass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
+                render_pass.set_index_buffer(mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+                render_pass.draw_indexed(0..mesh.index_count, 0, 0..1);
+            }
+        }
+
+        context.queue.submit(std::iter::once(encoder.finish()));
+    }
+}
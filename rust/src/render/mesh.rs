@@ -302,6 +302,62 @@ impl Mesh {
         Self::from_line_data(device, &vertices, &indices)
     }
 
+    /// Top and bottom rings joined by `segments` vertical lines, centered on the
+    /// origin along Y.
+    pub fn wireframe_cylinder(device: &wgpu::Device, radius: f32, height: f32, segments: u32) -> Self {
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+
+        let half_height = height / 2.0;
+
+        for i in 0..segments {
+            let theta = 2.0 * PI * i as f32 / segments as f32;
+            let x = theta.cos() * radius;
+            let z = theta.sin() * radius;
+
+            vertices.push(Vertex {
+                position: [x, -half_height, z],
+                normal: [0.0, 1.0, 0.0],
+            });
+            vertices.push(Vertex {
+                position: [x, half_height, z],
+                normal: [0.0, 1.0, 0.0],
+            });
+
+            let base = i * 2;
+            let next = ((i + 1) % segments) * 2;
+            indices.extend_from_slice(&[base, base + 1, base, next, base + 1, next + 1]);
+        }
+
+        Self::from_line_data(device, &vertices, &indices)
+    }
+
+    /// Square grid in the XZ plane, spanning `-half_size..half_size` with `divisions`
+    /// cells a side.
+    pub fn wireframe_grid(device: &wgpu::Device, half_size: f32, divisions: u32) -> Self {
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+
+        for i in 0..=divisions {
+            let offset = -half_size + 2.0 * half_size * i as f32 / divisions as f32;
+            let base = vertices.len() as u32;
+            for position in [
+                [offset, 0.0, -half_size],
+                [offset, 0.0, half_size],
+                [-half_size, 0.0, offset],
+                [half_size, 0.0, offset],
+            ] {
+                vertices.push(Vertex {
+                    position,
+                    normal: [0.0, 1.0, 0.0],
+                });
+            }
+            indices.extend_from_slice(&[base, base + 1, base + 2, base + 3]);
+        }
+
+        Self::from_line_data(device, &vertices, &indices)
+    }
+
     pub fn from_line_data(device: &wgpu::Device, vertices: &[Vertex], indices: &[u32]) -> Self {
         use wgpu::util::DeviceExt;
 